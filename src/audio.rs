@@ -0,0 +1,492 @@
+use crate::{
+    game::{
+        events::{
+            AttackResolved, BattleEnded, BattleResult, BuddyBought, BuddyFainted, BuddyMoved,
+            BuddySold, BuddyUpgraded,
+        },
+        persist,
+    },
+    AppState,
+};
+#[cfg(not(target_arch = "wasm32"))]
+use bevy::audio::AudioLoader;
+use bevy::prelude::*;
+#[cfg(not(target_arch = "wasm32"))]
+use rodio::{OutputStream, OutputStreamHandle, Sink, Source};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+#[cfg(not(target_arch = "wasm32"))]
+use std::{io::Cursor, sync::Arc};
+
+// bevy_audio can't change the volume of (or stop) a sound once it is playing, so on desktop
+// playback goes straight through rodio sinks instead. bevy's own AudioPlugin is left out of
+// `DefaultPlugins` there, so only one output stream is opened. Browsers get the cues through
+// bevy_audio, without volume control or music.
+pub struct SoundPlugin;
+
+impl Plugin for SoundPlugin {
+    fn build(&self, app: &mut App) {
+        // with bevy's AudioPlugin left out, sounds still need to load
+        #[cfg(not(target_arch = "wasm32"))]
+        app.add_asset::<AudioSource>()
+            .init_asset_loader::<AudioLoader>()
+            .insert_non_send_resource(AudioBackend::new())
+            .add_system(play_music);
+
+        app.add_event::<SoundCue>()
+            .insert_resource(persist::load::<AudioSettings>(AudioSettings::SAVE_NAME))
+            .init_resource::<SoundAssets>()
+            .add_system(cue_sounds)
+            .add_system(play_sounds)
+            .add_system(save_audio_settings);
+    }
+}
+
+/// A request to play a sound effect. Gameplay events are mapped to these in [`cue_sounds`],
+/// which keeps "what should be heard" separate from actually playing it.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum SoundCue {
+    Buy,
+    Drop,
+    Trash,
+    Impact,
+    Faint,
+    Win,
+    Lose,
+    Tie,
+}
+
+impl SoundCue {
+    const ALL: &'static [SoundCue] = &[
+        SoundCue::Buy,
+        SoundCue::Drop,
+        SoundCue::Trash,
+        SoundCue::Impact,
+        SoundCue::Faint,
+        SoundCue::Win,
+        SoundCue::Lose,
+        SoundCue::Tie,
+    ];
+
+    pub fn get_path(&self) -> &'static str {
+        match self {
+            SoundCue::Buy => "sound/buy.wav",
+            SoundCue::Drop => "sound/drop.wav",
+            SoundCue::Trash => "sound/trash.wav",
+            SoundCue::Impact => "sound/impact.wav",
+            SoundCue::Faint => "sound/faint.wav",
+            SoundCue::Win => "sound/win.wav",
+            SoundCue::Lose => "sound/lose.wav",
+            SoundCue::Tie => "sound/tie.wav",
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum MusicTrack {
+    Menu,
+    Shop,
+    Battle,
+}
+
+impl MusicTrack {
+    const ALL: &'static [MusicTrack] = &[MusicTrack::Menu, MusicTrack::Shop, MusicTrack::Battle];
+
+    pub fn get_path(&self) -> &'static str {
+        match self {
+            MusicTrack::Menu => "sound/music/menu.wav",
+            MusicTrack::Shop => "sound/music/shop.wav",
+            MusicTrack::Battle => "sound/music/battle.wav",
+        }
+    }
+
+    /// `None` means "keep playing whatever is already playing"
+    pub fn for_state(state: &AppState) -> Option<MusicTrack> {
+        match state {
+            AppState::Menu => Some(MusicTrack::Menu),
+            AppState::Shop => Some(MusicTrack::Shop),
+            AppState::Battle => Some(MusicTrack::Battle),
+            AppState::Loading | AppState::Startup | AppState::Map => None,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Serialize, Deserialize)]
+pub struct AudioSettings {
+    pub master: f32,
+    pub music: f32,
+    pub sfx: f32,
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        Self {
+            master: 1.0,
+            music: 0.6,
+            sfx: 1.0,
+        }
+    }
+}
+
+impl AudioSettings {
+    const SAVE_NAME: &'static str = "audio_settings";
+
+    pub fn music_volume(&self) -> f32 {
+        self.master * self.music
+    }
+
+    pub fn sfx_volume(&self) -> f32 {
+        self.master * self.sfx
+    }
+}
+
+pub struct SoundAssets {
+    cues: HashMap<SoundCue, Handle<AudioSource>>,
+    // browsers don't get music, see SoundPlugin
+    #[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+    music: HashMap<MusicTrack, Handle<AudioSource>>,
+}
+
+impl FromWorld for SoundAssets {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.get_resource::<AssetServer>().unwrap();
+        Self {
+            cues: SoundCue::ALL
+                .iter()
+                .map(|cue| (*cue, asset_server.load(cue.get_path())))
+                .collect(),
+            music: MusicTrack::ALL
+                .iter()
+                .map(|track| (*track, asset_server.load(track.get_path())))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+struct AudioBackend {
+    // the stream itself has to stay alive for anything to play
+    output: Option<(OutputStream, OutputStreamHandle)>,
+    music_track: Option<MusicTrack>,
+    music_sink: Option<Sink>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl AudioBackend {
+    fn new() -> Self {
+        let output = match OutputStream::try_default() {
+            Ok(output) => Some(output),
+            Err(err) => {
+                warn!(
+                    "no audio output available, the game will be silent: {}",
+                    err
+                );
+                None
+            }
+        };
+        Self {
+            output,
+            music_track: None,
+            music_sink: None,
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn decode(source: &AudioSource) -> Option<rodio::Decoder<Cursor<Arc<[u8]>>>> {
+    match rodio::Decoder::new(Cursor::new(source.bytes.clone())) {
+        Ok(decoder) => Some(decoder),
+        Err(err) => {
+            warn!("failed to decode sound: {}", err);
+            None
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn new_sink(handle: &OutputStreamHandle, volume: f32) -> Option<Sink> {
+    match Sink::try_new(handle) {
+        Ok(sink) => {
+            sink.set_volume(volume);
+            Some(sink)
+        }
+        Err(err) => {
+            warn!("failed to create audio sink: {}", err);
+            None
+        }
+    }
+}
+
+pub fn cue_sounds(
+    mut bought_events: EventReader<BuddyBought>,
+    mut sold_events: EventReader<BuddySold>,
+    mut upgraded_events: EventReader<BuddyUpgraded>,
+    mut moved_events: EventReader<BuddyMoved>,
+    mut attack_events: EventReader<AttackResolved>,
+    mut fainted_events: EventReader<BuddyFainted>,
+    mut battle_ended_events: EventReader<BattleEnded>,
+    mut cues: EventWriter<SoundCue>,
+) {
+    // a bought buddy is dropped onto a slot as well
+    let mut dropped = false;
+    for _ in bought_events.iter() {
+        cues.send(SoundCue::Buy);
+        dropped = true;
+    }
+    for _ in sold_events.iter() {
+        cues.send(SoundCue::Trash);
+    }
+    for _ in upgraded_events.iter() {
+        cues.send(SoundCue::Buy);
+    }
+    // a swap moves two buddies and both front buddies hit each other at once,
+    // but each should only be heard once
+    if moved_events.iter().count() > 0 || dropped {
+        cues.send(SoundCue::Drop);
+    }
+    if attack_events.iter().count() > 0 {
+        cues.send(SoundCue::Impact);
+    }
+    for _ in fainted_events.iter() {
+        cues.send(SoundCue::Faint);
+    }
+    for battle_ended in battle_ended_events.iter() {
+        cues.send(match battle_ended.result {
+            BattleResult::Win => SoundCue::Win,
+            BattleResult::Lose => SoundCue::Lose,
+            BattleResult::Tie => SoundCue::Tie,
+        });
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn play_sounds(
+    backend: NonSend<AudioBackend>,
+    settings: Res<AudioSettings>,
+    sounds: Res<SoundAssets>,
+    sources: Res<Assets<AudioSource>>,
+    mut cues: EventReader<SoundCue>,
+) {
+    let volume = settings.sfx_volume();
+    for cue in cues.iter() {
+        let handle = match &backend.output {
+            Some((_, handle)) if volume > 0.0 => handle,
+            _ => continue,
+        };
+        if let Some(source) = sources.get(&sounds.cues[cue]) {
+            if let (Some(decoder), Some(sink)) = (decode(source), new_sink(handle, volume)) {
+                sink.append(decoder);
+                sink.detach();
+            }
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn play_sounds(
+    audio: Res<Audio>,
+    settings: Res<AudioSettings>,
+    sounds: Res<SoundAssets>,
+    mut cues: EventReader<SoundCue>,
+) {
+    // bevy_audio plays everything at full volume, so only muting is honored
+    let muted = settings.sfx_volume() <= 0.0;
+    for cue in cues.iter() {
+        if !muted {
+            audio.play(sounds.cues[cue].clone());
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn play_music(
+    mut backend: NonSendMut<AudioBackend>,
+    state: Res<State<AppState>>,
+    settings: Res<AudioSettings>,
+    sounds: Res<SoundAssets>,
+    sources: Res<Assets<AudioSource>>,
+) {
+    let backend = &mut *backend;
+    if settings.is_changed() {
+        if let Some(sink) = &backend.music_sink {
+            sink.set_volume(settings.music_volume());
+        }
+    }
+
+    let track = match MusicTrack::for_state(state.current()) {
+        Some(track) if backend.music_track != Some(track) => track,
+        _ => return,
+    };
+    let handle = match &backend.output {
+        Some((_, handle)) => handle,
+        None => return,
+    };
+    // wait for the track to load instead of skipping it
+    if let Some(source) = sources.get(&sounds.music[&track]) {
+        // dropping the old sink stops the old track
+        backend.music_sink = None;
+        if let (Some(decoder), Some(sink)) =
+            (decode(source), new_sink(handle, settings.music_volume()))
+        {
+            sink.append(decoder.repeat_infinite());
+            backend.music_sink = Some(sink);
+        }
+        backend.music_track = Some(track);
+    }
+}
+
+fn save_audio_settings(settings: Res<AudioSettings>) {
+    if settings.is_changed() && !settings.is_added() {
+        persist::save(AudioSettings::SAVE_NAME, &*settings);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{
+        buddy::{BuddyColor, Side},
+        events::{BuddyLabel, EventsPlugin},
+        name::BuddyName,
+        workshop::Upgrade,
+    };
+    use bevy::ecs::event::Events;
+
+    /// just the cue layer, so no audio device is needed
+    fn cue_app() -> App {
+        let mut app = App::new();
+        app.add_plugin(EventsPlugin)
+            .add_event::<SoundCue>()
+            .add_system(cue_sounds);
+        app
+    }
+
+    fn send<T: Send + Sync + 'static>(app: &mut App, event: T) {
+        app.world
+            .get_resource_mut::<Events<T>>()
+            .unwrap()
+            .send(event);
+    }
+
+    fn label() -> BuddyLabel {
+        BuddyLabel {
+            name: BuddyName("Pip".to_string()),
+            color: BuddyColor::Red,
+        }
+    }
+
+    /// runs a frame and returns every cue it requested
+    fn cues(app: &mut App) -> Vec<SoundCue> {
+        app.update();
+        let events = app.world.get_resource::<Events<SoundCue>>().unwrap();
+        events.get_reader().iter(events).copied().collect()
+    }
+
+    #[test]
+    fn buying_lands_the_buddy_in_a_slot() {
+        let mut app = cue_app();
+        let buddy = app.world.spawn().id();
+        send(&mut app, BuddyBought { buddy, price: 2 });
+        assert_eq!(cues(&mut app), vec![SoundCue::Buy, SoundCue::Drop]);
+    }
+
+    #[test]
+    fn a_swap_drops_once() {
+        let mut app = cue_app();
+        let first = app.world.spawn().id();
+        let second = app.world.spawn().id();
+        send(
+            &mut app,
+            BuddyMoved {
+                buddy: first,
+                from: 0,
+                to: 1,
+            },
+        );
+        send(
+            &mut app,
+            BuddyMoved {
+                buddy: second,
+                from: 1,
+                to: 0,
+            },
+        );
+        assert_eq!(cues(&mut app), vec![SoundCue::Drop]);
+    }
+
+    #[test]
+    fn shop_actions() {
+        let mut app = cue_app();
+        let buddy = app.world.spawn().id();
+        send(&mut app, BuddySold { buddy });
+        assert_eq!(cues(&mut app), vec![SoundCue::Trash]);
+
+        let mut app = cue_app();
+        send(
+            &mut app,
+            BuddyUpgraded {
+                buddy,
+                upgrade: Upgrade::Health,
+                price: 3,
+            },
+        );
+        assert_eq!(cues(&mut app), vec![SoundCue::Buy]);
+    }
+
+    #[test]
+    fn an_exchange_is_one_impact() {
+        let mut app = cue_app();
+        let left = app.world.spawn().id();
+        let right = app.world.spawn().id();
+        for (attacker, target) in [(left, right), (right, left)] {
+            send(
+                &mut app,
+                AttackResolved {
+                    attacker,
+                    attacker_label: label(),
+                    target,
+                    target_label: label(),
+                    damage: 1,
+                    effective: false,
+                },
+            );
+        }
+        assert_eq!(cues(&mut app), vec![SoundCue::Impact]);
+    }
+
+    #[test]
+    fn every_faint_is_heard() {
+        let mut app = cue_app();
+        for side in [Side::Left, Side::Right] {
+            let buddy = app.world.spawn().id();
+            send(
+                &mut app,
+                BuddyFainted {
+                    buddy,
+                    side,
+                    label: label(),
+                },
+            );
+        }
+        assert_eq!(cues(&mut app), vec![SoundCue::Faint, SoundCue::Faint]);
+    }
+
+    #[test]
+    fn battle_results_have_their_own_jingles() {
+        for (result, cue) in [
+            (BattleResult::Win, SoundCue::Win),
+            (BattleResult::Lose, SoundCue::Lose),
+            (BattleResult::Tie, SoundCue::Tie),
+        ] {
+            let mut app = cue_app();
+            send(&mut app, BattleEnded { result, reward: 0 });
+            assert_eq!(cues(&mut app), vec![cue]);
+        }
+    }
+
+    #[test]
+    fn a_quiet_frame_is_silent() {
+        let mut app = cue_app();
+        assert!(cues(&mut app).is_empty());
+    }
+}
@@ -0,0 +1,290 @@
+//! Pairs up online clients and passes their locked in teams between them. It only listens on this
+//! machine, so a whole match can be played with two clients side by side.
+//!
+//! ```text
+//! cargo run --release --bin bab-relay -- --shop-seconds 30
+//! ```
+
+use build_a_better_buddy::game::{
+    hot_seat::{PerPlayer, Player},
+    net::{self, ClientMessage, RelayMessage, RELAY_HOST, RELAY_PORT, SHOP_SECONDS},
+    rules::Rules,
+};
+use std::{
+    env,
+    io::{self, BufReader},
+    net::{TcpListener, TcpStream},
+    process,
+    sync::mpsc::{self, Receiver, RecvTimeoutError},
+    thread,
+    time::{Duration, Instant},
+};
+
+const USAGE: &str = "usage: bab-relay [options]
+
+options:
+    --port <port>            port to listen on (default 7878)
+    --shop-seconds <secs>    how long players get to shop every round (default 45)";
+
+/// how long a new connection gets to say hello before it's dropped
+const HELLO_TIMEOUT: Duration = Duration::from_secs(10);
+/// clients only start their shop timers once the last battle has finished playing out, so their
+/// teams can arrive a while after the shop time is up
+const LOCK_IN_GRACE: Duration = Duration::from_secs(20);
+/// even a full battle watched at normal speed is over well before this
+const BATTLE_TIMEOUT: Duration = Duration::from_secs(180);
+
+struct Options {
+    port: u16,
+    shop_seconds: f32,
+}
+
+impl Options {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+        let mut options = Options {
+            port: RELAY_PORT,
+            shop_seconds: SHOP_SECONDS,
+        };
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or_else(|| format!("{} needs a value", arg));
+            match arg.as_str() {
+                "--port" => {
+                    let port = value()?;
+                    options.port = port
+                        .parse()
+                        .map_err(|_| format!("{} is not a port", port))?;
+                }
+                "--shop-seconds" => {
+                    let seconds = value()?;
+                    options.shop_seconds = seconds
+                        .parse()
+                        .ok()
+                        .filter(|seconds: &f32| *seconds > 0.0)
+                        .ok_or_else(|| format!("{} is not a number of seconds", seconds))?;
+                }
+                "--help" | "-h" => {
+                    println!("{}", USAGE);
+                    process::exit(0);
+                }
+                _ => return Err(format!("unknown option {}", arg)),
+            }
+        }
+        Ok(options)
+    }
+}
+
+/// A client that has said hello
+struct Client {
+    stream: TcpStream,
+    reader: BufReader<TcpStream>,
+    rules_version: u32,
+    rules: Rules,
+}
+
+impl Client {
+    fn greet(stream: TcpStream) -> io::Result<Client> {
+        stream.set_nodelay(true)?;
+        stream.set_read_timeout(Some(HELLO_TIMEOUT))?;
+        let mut reader = BufReader::new(stream.try_clone()?);
+        match net::receive(&mut reader)? {
+            Some(ClientMessage::Hello {
+                rules_version,
+                rules,
+            }) => {
+                stream.set_read_timeout(None)?;
+                Ok(Client {
+                    stream,
+                    reader,
+                    rules_version,
+                    rules,
+                })
+            }
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "expected a hello",
+            )),
+        }
+    }
+
+    /// both clients have to resolve battles the same way
+    fn can_play(&self, other: &Client) -> bool {
+        self.rules_version == other.rules_version && self.rules == other.rules
+    }
+}
+
+fn main() {
+    let options = match Options::parse(env::args().skip(1)) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("{}\n\n{}", err, USAGE);
+            process::exit(2);
+        }
+    };
+
+    let listener = match TcpListener::bind((RELAY_HOST, options.port)) {
+        Ok(listener) => listener,
+        Err(err) => {
+            eprintln!("can't listen on {}:{}: {}", RELAY_HOST, options.port, err);
+            process::exit(1);
+        }
+    };
+    println!("relay listening on {}:{}", RELAY_HOST, options.port);
+
+    // greeting waits on the client, so it happens on its own thread and a connection that never
+    // says hello can't hold up everyone else
+    let (greeted, clients) = mpsc::channel();
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let greeted = greeted.clone();
+            thread::spawn(move || match stream.and_then(Client::greet) {
+                Ok(client) => {
+                    let _ = greeted.send(client);
+                }
+                Err(err) => eprintln!("dropped a connection: {}", err),
+            });
+        }
+    });
+
+    let mut waiting: Vec<Client> = Vec::new();
+    for mut client in clients {
+        match waiting.iter().position(|other| other.can_play(&client)) {
+            Some(index) => {
+                let other = waiting.remove(index);
+                let shop_seconds = options.shop_seconds;
+                println!("starting a match");
+                thread::spawn(move || run_match([other, client], shop_seconds));
+            }
+            None => {
+                // clients that hung up while waiting are found out here, or when they're matched
+                if net::send(&mut client.stream, &RelayMessage::Waiting).is_ok() {
+                    waiting.push(client);
+                }
+            }
+        }
+    }
+}
+
+/// Something one of the clients did, passed on by its reader thread
+enum Event {
+    Message(Player, ClientMessage),
+    Left(Player),
+}
+
+/// Why a match stopped
+enum End {
+    Left(Player),
+    Desync,
+}
+
+struct Match {
+    streams: PerPlayer<Option<TcpStream>>,
+    events: Receiver<Event>,
+}
+
+fn run_match(clients: [Client; 2], shop_seconds: f32) {
+    let (sender, events) = mpsc::channel();
+    let mut streams = PerPlayer::default();
+    for (seat, client) in Player::ALL.into_iter().zip(clients) {
+        streams[seat] = Some(client.stream);
+        let sender = sender.clone();
+        let mut reader = client.reader;
+        thread::spawn(move || {
+            while let Ok(Some(message)) = net::receive(&mut reader) {
+                if sender.send(Event::Message(seat, message)).is_err() {
+                    return;
+                }
+            }
+            let _ = sender.send(Event::Left(seat));
+        });
+    }
+
+    let mut relay = Match { streams, events };
+    match relay.play(shop_seconds) {
+        Err(End::Left(seat)) => {
+            println!("{} left a match", seat.name());
+            let _ = relay.tell(seat.other(), &RelayMessage::OpponentLeft);
+        }
+        Err(End::Desync) => println!("a match ended in a desync"),
+        Ok(()) => {}
+    }
+}
+
+impl Match {
+    fn tell(&mut self, seat: Player, message: &RelayMessage) -> Result<(), End> {
+        match &mut self.streams[seat] {
+            Some(stream) => net::send(stream, message).map_err(|_| End::Left(seat)),
+            None => Err(End::Left(seat)),
+        }
+    }
+
+    fn broadcast(&mut self, message: &RelayMessage) -> Result<(), End> {
+        for seat in Player::ALL {
+            self.tell(seat, message)?;
+        }
+        Ok(())
+    }
+
+    /// Waits for one message from each client that `pick` accepts, ignoring anything else. Clients
+    /// that don't send one before `deadline` get `None`.
+    fn collect<T>(
+        &self,
+        deadline: Instant,
+        pick: impl Fn(ClientMessage) -> Option<T>,
+    ) -> Result<PerPlayer<Option<T>>, End> {
+        let mut received: PerPlayer<Option<T>> = PerPlayer::default();
+        while Player::ALL.iter().any(|seat| received[*seat].is_none()) {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            match self.events.recv_timeout(timeout) {
+                Ok(Event::Message(seat, message)) => {
+                    if let Some(value) = pick(message) {
+                        received[seat] = Some(value);
+                    }
+                }
+                Ok(Event::Left(seat)) => return Err(End::Left(seat)),
+                Err(RecvTimeoutError::Timeout | RecvTimeoutError::Disconnected) => break,
+            }
+        }
+        Ok(received)
+    }
+
+    /// Plays rounds until someone leaves or the clients stop agreeing
+    fn play(&mut self, shop_seconds: f32) -> Result<(), End> {
+        for seat in Player::ALL {
+            self.tell(seat, &RelayMessage::Matched { seat })?;
+        }
+
+        let mut round = 0;
+        loop {
+            round += 1;
+            self.broadcast(&RelayMessage::ShopOpen {
+                round,
+                seconds: shop_seconds,
+            })?;
+            let deadline = Instant::now() + Duration::from_secs_f32(shop_seconds) + LOCK_IN_GRACE;
+            let mut teams = self.collect(deadline, |message| match message {
+                ClientMessage::LockIn { team } => Some(team),
+                _ => None,
+            })?;
+            // a client that never locked in fights with nobody
+            self.broadcast(&RelayMessage::Fight {
+                left: teams[Player::One].take().unwrap_or_default(),
+                right: teams[Player::Two].take().unwrap_or_default(),
+            })?;
+
+            let results =
+                self.collect(Instant::now() + BATTLE_TIMEOUT, |message| match message {
+                    ClientMessage::Finished { result } => Some(result),
+                    _ => None,
+                })?;
+            match (results[Player::One], results[Player::Two]) {
+                (Some(one), Some(two)) if one == two => {
+                    self.broadcast(&RelayMessage::Verified { result: one })?;
+                }
+                _ => {
+                    let _ = self.broadcast(&RelayMessage::Desync);
+                    return Err(End::Desync);
+                }
+            }
+        }
+    }
+}
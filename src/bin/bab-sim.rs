@@ -0,0 +1,399 @@
+//! Fights thousands of battles without a window and reports how they went, for balancing rewards
+//! and buddy stats without playing through every round by hand.
+//!
+//! ```text
+//! cargo run --release --bin bab-sim -- --battles 5000 --rounds 12 --format json
+//! ```
+
+use build_a_better_buddy::game::{
+    battle::opponent_for_round,
+    boss::{Boss, BossAbility},
+    buddy::{BuddyTemplate, Slot},
+    events::BattleResult,
+    replay::Replay,
+    rules::Rules,
+    shop::{BUDDY_PRICE, SHOP_ROUNDS_AHEAD, STARTING_COINS},
+    sim::{self, Outcome},
+};
+use rand::seq::SliceRandom;
+use std::{env, process};
+
+const USAGE: &str = "usage: bab-sim [options]
+
+options:
+    --battles <n>        battles per round and team kind (default 1000)
+    --rounds <n>         rounds to simulate (default 10)
+    --teams <kind>       random, ghost, bot or all (default all)
+    --format <format>    csv or json (default csv)
+    --color-advantage    turn on the color advantage rule";
+
+type Team = Vec<(BuddyTemplate, Option<BossAbility>)>;
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum TeamKind {
+    /// fresh `random_for_round` buddies, like the opponents get
+    Random,
+    /// teams players actually fought with, taken from the saved replays
+    Ghost,
+    /// a bot that shops its way through a whole run
+    Bot,
+}
+
+impl TeamKind {
+    const ALL: &'static [TeamKind] = &[TeamKind::Random, TeamKind::Ghost, TeamKind::Bot];
+
+    fn name(&self) -> &'static str {
+        match self {
+            TeamKind::Random => "random",
+            TeamKind::Ghost => "ghost",
+            TeamKind::Bot => "bot",
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Format {
+    Csv,
+    Json,
+}
+
+struct Options {
+    battles: usize,
+    rounds: usize,
+    teams: Vec<TeamKind>,
+    format: Format,
+    rules: Rules,
+}
+
+impl Options {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+        let mut options = Options {
+            battles: 1000,
+            rounds: 10,
+            teams: TeamKind::ALL.to_vec(),
+            format: Format::Csv,
+            rules: Rules::default(),
+        };
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or_else(|| format!("{} needs a value", arg));
+            match arg.as_str() {
+                "--battles" => options.battles = parse_number(&value()?)?,
+                "--rounds" => options.rounds = parse_number(&value()?)?,
+                "--teams" => {
+                    let teams = value()?;
+                    options.teams = match teams.as_str() {
+                        "all" => TeamKind::ALL.to_vec(),
+                        name => vec![*TeamKind::ALL
+                            .iter()
+                            .find(|kind| kind.name() == name)
+                            .ok_or_else(|| format!("unknown team kind {}", name))?],
+                    };
+                }
+                "--format" => {
+                    options.format = match value()?.as_str() {
+                        "csv" => Format::Csv,
+                        "json" => Format::Json,
+                        format => return Err(format!("unknown format {}", format)),
+                    };
+                }
+                "--color-advantage" => options.rules.color_advantage = true,
+                "--help" | "-h" => {
+                    println!("{}", USAGE);
+                    process::exit(0);
+                }
+                _ => return Err(format!("unknown option {}", arg)),
+            }
+        }
+        Ok(options)
+    }
+}
+
+fn parse_number(value: &str) -> Result<usize, String> {
+    value
+        .parse()
+        .map_err(|_| format!("{} is not a number", value))
+}
+
+/// min, max and mean of a stat, over every buddy it was measured on
+#[derive(Default)]
+struct Distribution {
+    count: usize,
+    total: usize,
+    min: Option<usize>,
+    max: usize,
+}
+
+impl Distribution {
+    fn add(&mut self, value: usize) {
+        self.count += 1;
+        self.total += value;
+        self.min = Some(self.min.map_or(value, |min| min.min(value)));
+        self.max = self.max.max(value);
+    }
+
+    fn mean(&self) -> f64 {
+        ratio(self.total, self.count)
+    }
+}
+
+#[derive(Default)]
+struct Tally {
+    battles: usize,
+    wins: usize,
+    losses: usize,
+    ties: usize,
+    exchanges: usize,
+    rewards: usize,
+    health: Distribution,
+    strength: Distribution,
+    foe_health: Distribution,
+    foe_strength: Distribution,
+}
+
+impl Tally {
+    fn add(
+        &mut self,
+        rules: &Rules,
+        team: &Team,
+        foe: &Team,
+        boss: Option<Boss>,
+        outcome: Outcome,
+    ) {
+        self.battles += 1;
+        match outcome.result {
+            BattleResult::Win => self.wins += 1,
+            BattleResult::Lose => self.losses += 1,
+            BattleResult::Tie => self.ties += 1,
+        }
+        self.exchanges += outcome.exchanges;
+        self.rewards += sim::reward(rules, outcome.result, boss);
+        for (template, _) in team {
+            self.health.add(template.health());
+            self.strength.add(template.strength());
+        }
+        for (template, _) in foe {
+            self.foe_health.add(template.health());
+            self.foe_strength.add(template.strength());
+        }
+    }
+}
+
+struct Row {
+    kind: TeamKind,
+    round: usize,
+    tally: Tally,
+}
+
+fn ratio(part: usize, total: usize) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        part as f64 / total as f64
+    }
+}
+
+fn main() {
+    let options = match Options::parse(env::args().skip(1)) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("{}\n\n{}", err, USAGE);
+            process::exit(2);
+        }
+    };
+
+    let mut rows = Vec::new();
+    for kind in options.teams.iter().copied() {
+        let tallies = match kind {
+            TeamKind::Random => simulate_random(&options),
+            TeamKind::Ghost => simulate_ghosts(&options),
+            TeamKind::Bot => simulate_bots(&options),
+        };
+        for (round, tally) in (1..).zip(tallies) {
+            // rounds without any ghosts to fight with are left out
+            if tally.battles > 0 {
+                rows.push(Row { kind, round, tally });
+            }
+        }
+    }
+
+    match options.format {
+        Format::Csv => print_csv(&rows),
+        Format::Json => print_json(&rows),
+    }
+}
+
+/// fights this round's opponent and returns the coins the battle paid out
+fn fight_round(rules: &Rules, round: usize, team: &Team, tally: &mut Tally) -> usize {
+    let (boss, foe) = opponent_for_round(&mut rand::thread_rng(), rules, round);
+    let outcome = sim::fight(rules, team, &foe);
+    tally.add(rules, team, &foe, boss, outcome);
+    sim::reward(rules, outcome.result, boss)
+}
+
+fn simulate_random(options: &Options) -> Vec<Tally> {
+    let mut rng = rand::thread_rng();
+    (1..=options.rounds)
+        .map(|round| {
+            let mut tally = Tally::default();
+            for _ in 0..options.battles {
+                let team = (0..Slot::MAX_PER_SIDE)
+                    .map(|_| (BuddyTemplate::random_for_round(&mut rng, round), None))
+                    .collect();
+                fight_round(&options.rules, round, &team, &mut tally);
+            }
+            tally
+        })
+        .collect()
+}
+
+fn simulate_ghosts(options: &Options) -> Vec<Tally> {
+    let replays = Replay::load_all();
+    let mut rng = rand::thread_rng();
+    (1..=options.rounds)
+        .map(|round| {
+            let mut tally = Tally::default();
+            let ghosts = replays
+                .iter()
+                .filter(|replay| replay.round == round && !replay.left.is_empty())
+                .collect::<Vec<_>>();
+            if ghosts.is_empty() {
+                return tally;
+            }
+            for _ in 0..options.battles {
+                let ghost = ghosts.choose(&mut rng).unwrap();
+                let team = ghost
+                    .left
+                    .iter()
+                    .map(|template| (template.clone(), None))
+                    .collect();
+                fight_round(&options.rules, round, &team, &mut tally);
+            }
+            tally
+        })
+        .collect()
+}
+
+/// Every bot plays a whole run. It buys whatever it can afford, swaps its weakest buddy out for
+/// better ones once the team is full, and keeps its healthiest buddy in front. It never uses the
+/// workshop.
+fn simulate_bots(options: &Options) -> Vec<Tally> {
+    let mut tallies = (0..options.rounds)
+        .map(|_| Tally::default())
+        .collect::<Vec<_>>();
+    let score = |template: &BuddyTemplate| template.health() + template.strength();
+    for _ in 0..options.battles {
+        let mut rng = rand::thread_rng();
+        let mut coins = STARTING_COINS;
+        let mut team: Vec<BuddyTemplate> = Vec::new();
+        for (round, tally) in (1..).zip(tallies.iter_mut()) {
+            // the shop rolls its buddies before the round counter goes up
+            let mut offers = (0..options.rules.shop_slots())
+                .map(|_| {
+                    let template =
+                        BuddyTemplate::random_for_round(&mut rng, round - 1 + SHOP_ROUNDS_AHEAD);
+                    options.rules.restyle(template)
+                })
+                .collect::<Vec<_>>();
+            offers.sort_by_key(|template| std::cmp::Reverse(score(template)));
+            for offer in offers {
+                if coins < BUDDY_PRICE {
+                    break;
+                }
+                if team.len() < Slot::MAX_PER_SIDE {
+                    team.push(offer);
+                    coins -= BUDDY_PRICE;
+                } else if let Some(weakest) = (0..team.len()).min_by_key(|i| score(&team[*i])) {
+                    if score(&offer) > score(&team[weakest]) {
+                        team[weakest] = offer;
+                        coins -= BUDDY_PRICE;
+                    }
+                }
+            }
+            team.sort_by_key(|template| std::cmp::Reverse(template.health()));
+
+            let fighting = team
+                .iter()
+                .map(|template| (template.clone(), None))
+                .collect();
+            coins += fight_round(&options.rules, round, &fighting, tally);
+        }
+    }
+    tallies
+}
+
+const COLUMNS: &[&str] = &[
+    "teams",
+    "round",
+    "battles",
+    "win_rate",
+    "lose_rate",
+    "tie_rate",
+    "avg_exchanges",
+    "avg_reward",
+    "health_mean",
+    "health_min",
+    "health_max",
+    "strength_mean",
+    "strength_min",
+    "strength_max",
+    "foe_health_mean",
+    "foe_health_min",
+    "foe_health_max",
+    "foe_strength_mean",
+    "foe_strength_min",
+    "foe_strength_max",
+];
+
+/// the values of a row, lined up with [`COLUMNS`]. The team kind is the only string.
+fn values(row: &Row) -> Vec<String> {
+    let tally = &row.tally;
+    let mut values = vec![
+        row.kind.name().to_string(),
+        row.round.to_string(),
+        tally.battles.to_string(),
+        format!("{:.4}", ratio(tally.wins, tally.battles)),
+        format!("{:.4}", ratio(tally.losses, tally.battles)),
+        format!("{:.4}", ratio(tally.ties, tally.battles)),
+        format!("{:.2}", ratio(tally.exchanges, tally.battles)),
+        format!("{:.2}", ratio(tally.rewards, tally.battles)),
+    ];
+    for distribution in [
+        &tally.health,
+        &tally.strength,
+        &tally.foe_health,
+        &tally.foe_strength,
+    ] {
+        values.push(format!("{:.2}", distribution.mean()));
+        values.push(distribution.min.unwrap_or(0).to_string());
+        values.push(distribution.max.to_string());
+    }
+    values
+}
+
+fn print_csv(rows: &[Row]) {
+    println!("{}", COLUMNS.join(","));
+    for row in rows {
+        println!("{}", values(row).join(","));
+    }
+}
+
+fn print_json(rows: &[Row]) {
+    println!("[");
+    for (index, row) in rows.iter().enumerate() {
+        let fields = COLUMNS
+            .iter()
+            .zip(values(row))
+            .enumerate()
+            .map(|(column, (name, value))| {
+                if column == 0 {
+                    format!("\"{}\": \"{}\"", name, value)
+                } else {
+                    format!("\"{}\": {}", name, value)
+                }
+            })
+            .collect::<Vec<_>>();
+        let comma = if index + 1 < rows.len() { "," } else { "" };
+        println!("  {{{}}}{}", fields.join(", "), comma);
+    }
+    println!("]");
+}
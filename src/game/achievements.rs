@@ -0,0 +1,247 @@
+use crate::{
+    game::{
+        buddy::{Buddy, Side},
+        campaign::Campaign,
+        counters::Trophies,
+        events::{BattleEnded, BattleResult, BuddyFainted},
+        hot_seat::{Player, Players},
+        persist,
+        puzzle::PuzzleRun,
+        replay::ReplayPlayback,
+        stats::Stats,
+        ui::UiRoot,
+    },
+    loading::GameAssets,
+};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+pub struct AchievementsPlugin;
+
+impl Plugin for AchievementsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(persist::load::<AchievementProgress>(
+            AchievementProgress::SAVE_NAME,
+        ))
+        .init_resource::<BattleTally>()
+        .add_system(track_achievements)
+        .add_system(update_toasts);
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Achievement {
+    LoneWolf,
+    TrophyCase,
+    Flawless,
+    BigSpender,
+    ToughLove,
+}
+
+impl Achievement {
+    pub const ALL: &'static [Achievement] = &[
+        Achievement::LoneWolf,
+        Achievement::TrophyCase,
+        Achievement::Flawless,
+        Achievement::BigSpender,
+        Achievement::ToughLove,
+    ];
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            Achievement::LoneWolf => "Lone Wolf",
+            Achievement::TrophyCase => "Trophy Case",
+            Achievement::Flawless => "Flawless",
+            Achievement::BigSpender => "Big Spender",
+            Achievement::ToughLove => "Tough Love",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Achievement::LoneWolf => "Win a battle with a single buddy",
+            Achievement::TrophyCase => "Reach 10 trophies",
+            Achievement::Flawless => "Win a battle without losing a buddy",
+            Achievement::BigSpender => "Buy 50 buddies",
+            Achievement::ToughLove => "Trash 10 buddies",
+        }
+    }
+}
+
+const TROPHY_CASE_TROPHIES: usize = 10;
+const BIG_SPENDER_BUDDIES: usize = 50;
+const TOUGH_LOVE_BUDDIES: usize = 10;
+
+/// Unlocked achievements, persisted across runs
+#[derive(Default, Serialize, Deserialize)]
+pub struct AchievementProgress {
+    pub unlocked: Vec<Achievement>,
+}
+
+impl AchievementProgress {
+    pub const SAVE_NAME: &'static str = "achievements";
+
+    pub fn is_unlocked(&self, achievement: Achievement) -> bool {
+        self.unlocked.contains(&achievement)
+    }
+
+    /// returns true if the achievement was newly unlocked
+    fn unlock(&mut self, achievement: Achievement) -> bool {
+        if self.is_unlocked(achievement) {
+            false
+        } else {
+            self.unlocked.push(achievement);
+            true
+        }
+    }
+}
+
+/// Tracks what happened to the player's team during the current battle
+#[derive(Default)]
+struct BattleTally {
+    left_fainted: usize,
+}
+
+fn track_achievements(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    mut progress: ResMut<AchievementProgress>,
+    mut tally: ResMut<BattleTally>,
+    stats: Res<Stats>,
+    trophies: Res<Trophies>,
+    mut fainted_events: EventReader<BuddyFainted>,
+    mut battle_ended_events: EventReader<BattleEnded>,
+    buddies: Query<&Side, With<Buddy>>,
+    ui_root: Query<Entity, With<UiRoot>>,
+    toasts: Query<&Toast>,
+    playback: Option<Res<ReplayPlayback>>,
+    puzzle: Option<Res<PuzzleRun>>,
+    campaign: Option<Res<Campaign>>,
+    players: Res<Players>,
+) {
+    // only the battles that count towards the stats count towards achievements
+    if playback.is_some() || puzzle.is_some() || campaign.is_some() || !players.keeps_score() {
+        return;
+    }
+
+    let mut unlocked = Vec::new();
+
+    if stats.is_changed() {
+        if stats.buddies_bought >= BIG_SPENDER_BUDDIES {
+            unlocked.push(Achievement::BigSpender);
+        }
+        if stats.buddies_trashed >= TOUGH_LOVE_BUDDIES {
+            unlocked.push(Achievement::ToughLove);
+        }
+    }
+
+    if trophies.is_changed() && trophies.won[Player::One] >= TROPHY_CASE_TROPHIES {
+        unlocked.push(Achievement::TrophyCase);
+    }
+
+    for fainted in fainted_events.iter() {
+        if fainted.side == Side::Left {
+            tally.left_fainted += 1;
+        }
+    }
+
+    for battle_ended in battle_ended_events.iter() {
+        if battle_ended.result == BattleResult::Win {
+            let team_size = buddies.iter().filter(|side| **side == Side::Left).count();
+            if team_size == 1 {
+                unlocked.push(Achievement::LoneWolf);
+            }
+            if tally.left_fainted == 0 {
+                unlocked.push(Achievement::Flawless);
+            }
+        }
+        *tally = BattleTally::default();
+    }
+
+    let mut changed = false;
+    let mut toast_count = toasts.iter().count();
+    for achievement in unlocked {
+        if progress.unlock(achievement) {
+            changed = true;
+            if let Ok(ui_root) = ui_root.get_single() {
+                spawn_toast(&mut commands, &assets, ui_root, achievement, toast_count);
+                toast_count += 1;
+            }
+        }
+    }
+
+    if changed {
+        persist::save(AchievementProgress::SAVE_NAME, &*progress);
+    }
+}
+
+#[derive(Component)]
+pub struct Toast(Timer);
+
+const TOAST_HEIGHT: f32 = 80.0;
+
+fn spawn_toast(
+    commands: &mut Commands,
+    assets: &GameAssets,
+    ui_root: Entity,
+    achievement: Achievement,
+    index: usize,
+) {
+    let font = assets.title_font.clone();
+    commands.entity(ui_root).with_children(|parent| {
+        parent
+            .spawn_bundle(NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Px(360.0), Val::Px(TOAST_HEIGHT - 8.0)),
+                    position_type: PositionType::Absolute,
+                    // ui space is y-up, so this stacks toasts down from the top of the screen
+                    position: Rect {
+                        bottom: Val::Px(90.0 + index as f32 * TOAST_HEIGHT),
+                        right: Val::Px(20.0),
+                        ..Default::default()
+                    },
+                    flex_direction: FlexDirection::ColumnReverse,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..Default::default()
+                },
+                color: Color::rgba(0.95, 0.95, 1.0, 0.9).into(),
+                ..Default::default()
+            })
+            .insert(Toast(Timer::from_seconds(4.0, false)))
+            .with_children(|parent| {
+                parent.spawn_bundle(TextBundle {
+                    text: Text::with_section(
+                        format!("Achievement unlocked: {}", achievement.title()),
+                        TextStyle {
+                            font: font.clone(),
+                            font_size: 36.0,
+                            color: Color::hex("323232").unwrap(),
+                        },
+                        Default::default(),
+                    ),
+                    ..Default::default()
+                });
+                parent.spawn_bundle(TextBundle {
+                    text: Text::with_section(
+                        achievement.description(),
+                        TextStyle {
+                            font,
+                            font_size: 24.0,
+                            color: Color::hex("505050").unwrap(),
+                        },
+                        Default::default(),
+                    ),
+                    ..Default::default()
+                });
+            });
+    });
+}
+
+fn update_toasts(mut commands: Commands, time: Res<Time>, mut toasts: Query<(Entity, &mut Toast)>) {
+    for (entity, mut toast) in toasts.iter_mut() {
+        if toast.0.tick(time.delta()).just_finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
@@ -30,7 +30,7 @@ impl AnimateRange {
         ));
     }
 
-    pub fn percent(&mut self) -> f32 {
+    pub fn percent(&self) -> f32 {
         self.timer.percent()
     }
 
@@ -38,11 +38,11 @@ impl AnimateRange {
         self.timer.reset();
     }
 
-    pub fn just_finished(&mut self) -> bool {
+    pub fn just_finished(&self) -> bool {
         self.timer.just_finished()
     }
 
-    pub fn finished(&mut self) -> bool {
+    pub fn finished(&self) -> bool {
         self.timer.finished()
     }
 
@@ -0,0 +1,138 @@
+use crate::{game::buddy::BuddyColor, loading::GameAssets};
+use bevy::prelude::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// The seed a buddy's looks are generated from. Everything here is derived from the seed
+/// (and the buddy's [`BuddyColor`]), so a buddy looks the same every time it is spawned from a
+/// save or a replay.
+#[derive(Component, Debug, Default, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Appearance {
+    pub seed: u64,
+}
+
+/// Hashes a seed and a salt into 64 unrelated looking bits. This is splitmix64 rather than a
+/// `rand` generator, because those don't promise stable output between versions, and anything
+/// rolled from a saved or shared seed has to come out the same every time.
+pub fn splitmix(seed: u64, salt: u64) -> u64 {
+    let mut x = seed.wrapping_add(salt.wrapping_mul(0x9e37_79b9_7f4a_7c15));
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
+
+// each trait gets its own salt so they don't all vary together
+const SALT_HUE: u64 = 1;
+const SALT_SATURATION: u64 = 2;
+const SALT_LIGHTNESS: u64 = 3;
+const SALT_SIZE: u64 = 4;
+const SALT_SHAPE: u64 = 5;
+const SALT_ACCESSORY: u64 = 6;
+
+impl Appearance {
+    pub fn random() -> Self {
+        Self::from_rng(&mut rand::thread_rng())
+    }
+
+    pub fn from_rng(rng: &mut impl Rng) -> Self {
+        Self { seed: rng.gen() }
+    }
+
+    /// A number in `0.0..1.0` that only depends on the seed and the salt
+    fn roll(&self, salt: u64) -> f32 {
+        (splitmix(self.seed, salt) >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    fn roll_range(&self, salt: u64, min: f32, max: f32) -> f32 {
+        min + self.roll(salt) * (max - min)
+    }
+
+    /// the body color, which stays close to the hue of the buddy's [`BuddyColor`]
+    pub fn tint(&self, color: &BuddyColor) -> Color {
+        let hue = color.hue() + self.roll_range(SALT_HUE, -18.0, 18.0);
+        Color::hsl(
+            hue.rem_euclid(360.0),
+            self.roll_range(SALT_SATURATION, 0.14, 0.3),
+            self.roll_range(SALT_LIGHTNESS, 0.55, 0.66),
+        )
+    }
+
+    pub fn size(&self) -> f32 {
+        self.roll_range(SALT_SIZE, 0.9, 1.1)
+    }
+
+    pub fn body_shape(&self) -> BodyShape {
+        let shapes = BodyShape::ALL;
+        shapes[(self.roll(SALT_SHAPE) * shapes.len() as f32) as usize]
+    }
+
+    pub fn accessory(&self) -> Option<Accessory> {
+        // about half of all buddies get an accessory
+        let roll = self.roll(SALT_ACCESSORY);
+        if roll < 0.5 {
+            return None;
+        }
+        let accessories = Accessory::ALL;
+        let index = ((roll - 0.5) * 2.0 * accessories.len() as f32) as usize;
+        Some(accessories[index.min(accessories.len() - 1)])
+    }
+
+    /// the scale of everything that makes up the buddy's body
+    pub fn scale(&self) -> Vec3 {
+        let stretch = self.body_shape().stretch();
+        (stretch * self.size()).extend(1.0)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum BodyShape {
+    Round,
+    Tall,
+    Wide,
+}
+
+impl BodyShape {
+    const ALL: &'static [BodyShape] = &[BodyShape::Round, BodyShape::Tall, BodyShape::Wide];
+
+    fn stretch(&self) -> Vec2 {
+        match self {
+            BodyShape::Round => Vec2::new(1.0, 1.0),
+            BodyShape::Tall => Vec2::new(0.9, 1.12),
+            BodyShape::Wide => Vec2::new(1.12, 0.9),
+        }
+    }
+}
+
+/// Extra sprites drawn on top of (or behind) the body. The art shares the body's canvas, so it
+/// lines up without any offsets.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Accessory {
+    Hat,
+    Horns,
+    Ears,
+}
+
+impl Accessory {
+    const ALL: &'static [Accessory] = &[Accessory::Hat, Accessory::Horns, Accessory::Ears];
+
+    pub fn get_image(&self, assets: &GameAssets) -> Handle<Image> {
+        match self {
+            Accessory::Hat => assets.accessory_hat.clone(),
+            Accessory::Horns => assets.accessory_horns.clone(),
+            Accessory::Ears => assets.accessory_ears.clone(),
+        }
+    }
+
+    /// z relative to the body, horns and ears poke out from behind it
+    pub fn z_offset(&self) -> f32 {
+        match self {
+            Accessory::Hat => 0.3,
+            Accessory::Horns | Accessory::Ears => -0.1,
+        }
+    }
+
+    /// ears are part of the body, so they share its tint
+    pub fn is_tinted(&self) -> bool {
+        *self == Accessory::Ears
+    }
+}
@@ -3,20 +3,40 @@ use std::time::Duration;
 use crate::{
     game::{
         animate::{AnimateRange, Ease},
-        buddy::{Buddy, BuddyTemplate, Health, Offset, Side, Slot, Strength},
+        boss::{spawn_boss_intro, Boss, BossAbility},
+        buddy::{Buddy, BuddyColor, BuddyTemplate, Health, Offset, Side, Slot, Strength},
+        campaign::Campaign,
         counters::{Coins, Trophies},
+        daily::DailyChallenge,
+        events::{
+            AttackResolved, BattleEnded, BattleResult, BuddyFainted, BuddyLabel, BuddyMovedUp,
+        },
+        hot_seat::Players,
+        name::BuddyName,
+        online::Online,
         pad::{pad_enter_battle, pad_exit_battle, position_pad, PAD_SPACING},
+        puzzle::PuzzleRun,
+        replay::{Recording, Replay, ReplayPlayback},
+        rules::Rules,
+        run_rng::RunRng,
+        save::{team_templates, TeamQuery},
+        sim,
+        synergy::Shield,
         BattleMessages,
     },
+    loading::GameAssets,
     AppState,
 };
 use bevy::prelude::*;
+use rand::Rng;
 
 pub struct BattlePlugin;
 
 impl Plugin for BattlePlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<Battle>()
+            .init_resource::<BattleClock>()
+            .add_system_to_stage(CoreStage::PreUpdate, tick_battle_clock)
             .add_system_set(
                 SystemSet::on_enter(AppState::Battle)
                     .with_system(pad_enter_battle)
@@ -31,10 +51,29 @@ impl Plugin for BattlePlugin {
                 SystemSet::on_exit(AppState::Battle)
                     .with_system(pad_exit_battle)
                     .with_system(exit_battle),
-            );
+            )
+            .add_system_set(SystemSet::on_enter(AppState::Shop).with_system(roll_opponent));
     }
 }
 
+/// How much battle time passes this frame. The battle reads this rather than [`Time`], so replays
+/// can slow it down or pause it, and tests can step it by hand.
+#[derive(Default)]
+pub struct BattleClock {
+    pub delta: Duration,
+}
+
+fn tick_battle_clock(
+    time: Res<Time>,
+    playback: Option<Res<ReplayPlayback>>,
+    mut clock: ResMut<BattleClock>,
+) {
+    clock.delta = match playback {
+        Some(playback) => playback.delta(time.delta()),
+        None => time.delta(),
+    };
+}
+
 pub enum Action {
     Begin {
         timer: Timer,
@@ -73,22 +112,124 @@ impl Default for Battle {
             action: Action::Begin {
                 timer: Timer::default(),
             },
+            boss: None,
+            opponent: Vec::new(),
+            campaign: false,
+            puzzle: false,
+            final_round: false,
         }
     }
 }
 
 pub struct Battle {
     action: Action,
+    /// the boss being fought this round, if any
+    boss: Option<Boss>,
+    /// the templates the opposing team was spawned from, front to back
+    opponent: Vec<(BuddyTemplate, Option<BossAbility>)>,
+    /// campaign battles are followed by the map instead of the shop
+    campaign: bool,
+    /// puzzle battles, like campaign ones, are practice and win no trophies
+    puzzle: bool,
+    /// the last battle of a daily challenge is followed by the menu
+    final_round: bool,
 }
 
-pub fn enter_battle(
+impl Battle {
+    /// how far into the pause before the first attack the battle is
+    pub fn beginning(&self) -> Option<f32> {
+        match &self.action {
+            Action::Begin { timer } if !timer.finished() => Some(timer.percent()),
+            _ => None,
+        }
+    }
+
+    /// the two front buddies while they are lunging at each other
+    pub fn lunging(&self) -> Option<(Entity, Entity)> {
+        match &self.action {
+            Action::ExecuteAttack {
+                left_buddy,
+                right_buddy,
+                animate_in,
+                ..
+            } if !animate_in.finished() => Some((*left_buddy, *right_buddy)),
+            _ => None,
+        }
+    }
+
+    /// sets up the opposing team for the next battle and spawns it
+    pub fn spawn_opponent(
+        &mut self,
+        commands: &mut Commands,
+        assets: &GameAssets,
+        boss: Option<Boss>,
+        opponent: Vec<(BuddyTemplate, Option<BossAbility>)>,
+        transform: Transform,
+    ) {
+        for (slot, (template, ability)) in opponent.iter().enumerate() {
+            let entity = template
+                .clone()
+                .spawn(commands, assets, slot, Side::Right, transform);
+            if let Some(ability) = ability {
+                commands.entity(entity).insert(*ability);
+            }
+        }
+        self.boss = boss;
+        self.opponent = opponent;
+    }
+}
+
+/// A boss team on boss rounds, otherwise random buddies that get stronger every round
+pub fn opponent_for_round(
+    rng: &mut impl Rng,
+    rules: &Rules,
+    round: usize,
+) -> (Option<Boss>, Vec<(BuddyTemplate, Option<BossAbility>)>) {
+    let boss = Boss::for_round(round);
+    let opponent = match boss {
+        Some(boss) => boss
+            .team(rng, round)
+            .into_iter()
+            .map(|(template, ability)| (rules.restyle(template), Some(ability)))
+            .collect(),
+        None => (0..Slot::MAX_PER_SIDE)
+            .map(|_| {
+                let template = BuddyTemplate::random_for_round(rng, round);
+                (rules.restyle(template), None)
+            })
+            .collect(),
+    };
+    (boss, opponent)
+}
+
+/// The next opponent is rolled when the shop opens, so it can be previewed while shopping
+pub fn roll_opponent(
     mut commands: Commands,
-    asset_server: Res<AssetServer>,
+    assets: Res<GameAssets>,
     mut battle: ResMut<Battle>,
-    mut trophies: ResMut<Trophies>,
+    trophies: Res<Trophies>,
+    players: Res<Players>,
+    rules: Res<Rules>,
+    mut run_rng: ResMut<RunRng>,
+    online: Option<Res<Online>>,
+    puzzle: Option<Res<PuzzleRun>>,
+    campaign: Option<Res<Campaign>>,
+    daily: Option<Res<DailyChallenge>>,
     buddies: Query<(Entity, &Side), With<Buddy>>,
 ) {
-    trophies.rounds += 1;
+    battle.campaign = campaign.is_some();
+    battle.puzzle = puzzle.is_some();
+    // the round counter goes up when the battle starts
+    battle.final_round = daily.is_some() && trophies.rounds + 1 >= DailyChallenge::ROUNDS;
+
+    // in a versus game the right team belongs to the second player, and in the sandbox it's
+    // built by hand
+    if players.owns_both_teams() {
+        battle.boss = None;
+        battle.opponent.clear();
+        return;
+    }
+
     // clean up old battle entities
     for (entity, side) in buddies.iter() {
         if *side == Side::Right {
@@ -96,23 +237,102 @@ pub fn enter_battle(
         }
     }
 
-    for i in 0..Slot::MAX_PER_SIDE {
-        let template = BuddyTemplate::random_for_round(trophies.rounds);
-        template.spawn(
+    // online opponents arrive from the relay once both teams are locked in
+    if online.is_some_and(|online| online.playing()) {
+        return;
+    }
+
+    // a puzzle's enemy is the same on every attempt
+    if let Some(puzzle) = puzzle {
+        battle.spawn_opponent(
             &mut commands,
-            &asset_server,
-            i,
-            Side::Right,
-            Transform::default(),
+            &assets,
+            None,
+            puzzle.enemy(),
+            Transform::from_xyz(900.0, 230.0, 0.0),
         );
+        return;
+    }
+
+    // a campaign opponent brings the same team to every rematch
+    if let Some(campaign) = campaign {
+        battle.spawn_opponent(
+            &mut commands,
+            &assets,
+            None,
+            campaign.team(),
+            Transform::from_xyz(900.0, 230.0, 0.0),
+        );
+        return;
+    }
+
+    // the round counter goes up when the battle starts
+    let (boss, opponent) = opponent_for_round(&mut run_rng.opponents, &rules, trophies.rounds + 1);
+    battle.spawn_opponent(
+        &mut commands,
+        &assets,
+        boss,
+        opponent,
+        // off screen to the right, so a previewed team slides in
+        Transform::from_xyz(900.0, 230.0, 0.0),
+    );
+}
+
+pub fn enter_battle(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    mut battle: ResMut<Battle>,
+    mut trophies: ResMut<Trophies>,
+    mut recording: ResMut<Recording>,
+    rules: Res<Rules>,
+    players: Res<Players>,
+    playback: Option<Res<ReplayPlayback>>,
+    buddies: TeamQuery,
+) {
+    // replays don't count towards the run
+    if playback.is_none() {
+        if players.owns_both_teams() {
+            battle.opponent = team_templates(&buddies, Side::Right)
+                .into_iter()
+                .map(|template| (template, None))
+                .collect();
+        }
+        trophies.rounds += 1;
+        recording.0 = Some(Replay {
+            number: Replay::next_number(),
+            rules_version: Rules::VERSION,
+            rules: (*rules).clone(),
+            round: trophies.rounds,
+            boss: battle.boss,
+            left: team_templates(&buddies, Side::Left),
+            right: battle.opponent.clone(),
+            // filled in once the battle ends, which is when the replay is saved
+            result: BattleResult::Tie,
+        });
     }
 
+    let begin_seconds = if let Some(boss) = battle.boss {
+        spawn_boss_intro(&mut commands, &assets, boss);
+        // give the boss intro some room
+        3.0
+    } else {
+        2.0
+    };
+
     battle.action = Action::Begin {
-        timer: Timer::from_seconds(2.0, false),
+        timer: Timer::from_seconds(begin_seconds, false),
     };
 }
 
-pub fn exit_battle(mut commands: Commands, buddies: Query<(Entity, &Side), With<Buddy>>) {
+pub fn exit_battle(
+    mut commands: Commands,
+    players: Res<Players>,
+    buddies: Query<(Entity, &Side), With<Buddy>>,
+) {
+    // the second player's (or the sandbox's) right team sticks around for the next round
+    if players.owns_both_teams() {
+        return;
+    }
     // clean up old battle entities
     for (entity, side) in buddies.iter() {
         if *side == Side::Right {
@@ -127,7 +347,15 @@ pub fn battle(
     mut state: ResMut<State<AppState>>,
     mut trophies: ResMut<Trophies>,
     mut coins: ResMut<Coins>,
-    time: Res<Time>,
+    players: Res<Players>,
+    mut attack_events: EventWriter<AttackResolved>,
+    // paired up to stay within the number of parameters a system can have
+    (mut fainted_events, mut moved_up_events): (
+        EventWriter<BuddyFainted>,
+        EventWriter<BuddyMovedUp>,
+    ),
+    mut battle_ended_events: EventWriter<BattleEnded>,
+    clock: Res<BattleClock>,
     mut buddies: Query<(
         Entity,
         &mut Buddy,
@@ -139,11 +367,26 @@ pub fn battle(
         &mut Slot,
     )>,
     mut messages: Query<(&mut Visibility, &mut Transform), Without<Buddy>>,
+    mut shields: Query<&mut Shield>,
+    rules: Res<Rules>,
+    traits: TraitQuery,
+    mut playback: Option<ResMut<ReplayPlayback>>,
 ) {
+    let boss = battle.boss;
+    let campaign = battle.campaign;
+    let wins_trophies = !campaign && !battle.puzzle;
+    let final_round = battle.final_round;
+    let replaying = playback.is_some();
+    // replays play back under the rules they were recorded with, and can be paused and sped up
+    let rules = match &playback {
+        Some(playback) => playback.replay.rules.clone(),
+        None => (*rules).clone(),
+    };
+    let delta = clock.delta;
     let mut next_action = None;
     match &mut battle.action {
         Action::Begin { timer } => {
-            if timer.tick(time.delta()).just_finished() {
+            if timer.tick(delta).just_finished() {
                 next_action = Some(Action::StartAttack)
             }
         }
@@ -184,6 +427,23 @@ pub fn battle(
                         false,
                     ),
                 });
+            } else {
+                // a team with nobody on it loses straight away, instead of waiting forever for
+                // a buddy to attack with
+                let result = battle_result(left_buddy.is_some(), right_buddy.is_some())
+                    .unwrap_or(BattleResult::Tie);
+                next_action = Some(end_battle(
+                    &rules,
+                    result,
+                    boss,
+                    replaying,
+                    wins_trophies,
+                    &players,
+                    &battle_messages,
+                    &mut trophies,
+                    &mut coins,
+                    &mut battle_ended_events,
+                ));
             }
         }
         Action::ExecuteAttack {
@@ -197,7 +457,7 @@ pub fn battle(
             animate_out,
         } => {
             if !animate_in.finished() {
-                let x = animate_in.tick(time.delta());
+                let x = animate_in.tick(delta);
                 if let Ok(mut offset) = buddies.get_component_mut::<Offset>(*left_buddy) {
                     offset.0.translation = Vec3::new(x, 0.0, 0.0);
                 }
@@ -205,17 +465,48 @@ pub fn battle(
                     offset.0.translation = Vec3::new(-x, 0.0, 0.0);
                 }
                 if animate_in.just_finished() {
+                    let (left_attack, left_effective) =
+                        attack_damage(&rules, &traits, *left_buddy, *right_buddy, *left_strength);
+                    let (right_attack, right_effective) =
+                        attack_damage(&rules, &traits, *right_buddy, *left_buddy, *right_strength);
+                    // hitting a buddy with thorns hurts
+                    let left_incoming = right_attack + thorns(&traits, *right_buddy);
+                    let right_incoming = left_attack + thorns(&traits, *left_buddy);
+                    let left_damage = shielded_damage(&mut shields, *left_buddy, left_incoming);
+                    let right_damage = shielded_damage(&mut shields, *right_buddy, right_incoming);
                     if let Ok(mut health) = buddies.get_component_mut::<Health>(*left_buddy) {
-                        health.0.remove(*right_strength);
+                        health.0.remove(left_damage);
                         *left_died = health.0.value() == 0;
+                        regenerate(&traits, *left_buddy, &mut health);
                     }
                     if let Ok(mut health) = buddies.get_component_mut::<Health>(*right_buddy) {
-                        health.0.remove(*left_strength);
+                        health.0.remove(right_damage);
                         *right_died = health.0.value() == 0;
+                        regenerate(&traits, *right_buddy, &mut health);
+                    }
+                    if let (Some(left_label), Some(right_label)) =
+                        (label(&traits, *left_buddy), label(&traits, *right_buddy))
+                    {
+                        attack_events.send(AttackResolved {
+                            attacker: *left_buddy,
+                            attacker_label: left_label.clone(),
+                            target: *right_buddy,
+                            target_label: right_label.clone(),
+                            damage: right_damage,
+                            effective: left_effective,
+                        });
+                        attack_events.send(AttackResolved {
+                            attacker: *right_buddy,
+                            attacker_label: right_label,
+                            target: *left_buddy,
+                            target_label: left_label,
+                            damage: left_damage,
+                            effective: right_effective,
+                        });
                     }
                 }
             } else {
-                let x = animate_out.tick(time.delta());
+                let x = animate_out.tick(delta);
                 if let Ok(mut offset) = buddies.get_component_mut::<Offset>(*left_buddy) {
                     offset.0.translation = Vec3::new(x, 0.0, 0.0);
                 }
@@ -245,7 +536,7 @@ pub fn battle(
             right_died,
             animate_shift,
         } => {
-            let x = animate_shift.tick(time.delta());
+            let x = animate_shift.tick(delta);
             let percent = animate_shift.percent();
             for (entity, _, _, _, _, mut offset, side, _) in buddies.iter_mut() {
                 if *left_died && *side == Side::Left {
@@ -270,9 +561,24 @@ pub fn battle(
                         if entity == *left_buddy {
                             buddy.alive = false;
                             slot.current = 10;
+                            if let Some(label) = label(&traits, entity) {
+                                fainted_events.send(BuddyFainted {
+                                    buddy: entity,
+                                    side: *side,
+                                    label,
+                                });
+                            }
                         } else if buddy.alive {
                             offset.0.translation = Vec3::new(0.0, 0.0, 0.0);
                             slot.current -= 1;
+                            if let Some(label) = label(&traits, entity) {
+                                moved_up_events.send(BuddyMovedUp {
+                                    buddy: entity,
+                                    side: *side,
+                                    label,
+                                    slot: slot.current,
+                                });
+                            }
                         }
                     }
 
@@ -280,9 +586,24 @@ pub fn battle(
                         if entity == *right_buddy {
                             buddy.alive = false;
                             slot.current = 10;
+                            if let Some(label) = label(&traits, entity) {
+                                fainted_events.send(BuddyFainted {
+                                    buddy: entity,
+                                    side: *side,
+                                    label,
+                                });
+                            }
                         } else if buddy.alive {
                             offset.0.translation = Vec3::new(0.0, 0.0, 0.0);
                             slot.current -= 1;
+                            if let Some(label) = label(&traits, entity) {
+                                moved_up_events.send(BuddyMovedUp {
+                                    buddy: entity,
+                                    side: *side,
+                                    label,
+                                    slot: slot.current,
+                                });
+                            }
                         }
                     }
                 }
@@ -298,41 +619,21 @@ pub fn battle(
                     }
                 }
 
-                let animate_in =
-                    AnimateRange::new(Duration::from_secs_f32(1.0), Ease::OutBack, 0.0..1.0, false);
-                let animate_out = AnimateRange::new(
-                    Duration::from_secs_f32(0.6),
-                    Ease::InOutCirc,
-                    1.0..0.0,
-                    false,
-                );
-                let action = match (left_alive, right_alive) {
-                    (true, true) => Action::StartAttack,
-                    (true, false) => {
-                        trophies.won += 1;
-                        coins.0 += 5;
-                        Action::ShowMessage {
-                            entity: battle_messages.you_win,
-                            animate_in,
-                            animate_out,
-                        }
-                    }
-                    (false, true) => {
-                        coins.0 += 2;
-                        Action::ShowMessage {
-                            entity: battle_messages.you_lose,
-                            animate_in,
-                            animate_out,
-                        }
-                    }
-                    (false, false) => {
-                        coins.0 += 3;
-                        Action::ShowMessage {
-                            entity: battle_messages.you_tie,
-                            animate_in,
-                            animate_out,
-                        }
-                    }
+                let action = if let Some(result) = battle_result(left_alive, right_alive) {
+                    end_battle(
+                        &rules,
+                        result,
+                        boss,
+                        replaying,
+                        wins_trophies,
+                        &players,
+                        &battle_messages,
+                        &mut trophies,
+                        &mut coins,
+                        &mut battle_ended_events,
+                    )
+                } else {
+                    Action::StartAttack
                 };
                 next_action = Some(action);
             }
@@ -344,9 +645,9 @@ pub fn battle(
         } => {
             let mut visible = true;
             let x = if !animate_in.finished() {
-                animate_in.tick(time.delta())
+                animate_in.tick(delta)
             } else {
-                let x = animate_out.tick(time.delta());
+                let x = animate_out.tick(delta);
                 if animate_out.just_finished() {
                     next_action = Some(Action::RestoreBuddies {
                         animate: AnimateRange::new(
@@ -366,13 +667,14 @@ pub fn battle(
             }
         }
         Action::RestoreBuddies { animate } => {
-            animate.tick(time.delta());
+            animate.tick(delta);
 
             if animate.just_finished() {
                 for (_, mut buddy, mut health, mut strength, _, mut offset, side, mut slot) in
                     buddies.iter_mut()
                 {
-                    if *side == Side::Left {
+                    // in a versus game or the sandbox both teams are kept for the next round
+                    if *side == Side::Left || (players.owns_both_teams() && *side == Side::Right) {
                         buddy.alive = true;
                         slot.reset();
                         health.0.reset();
@@ -380,12 +682,266 @@ pub fn battle(
                         *offset = Offset::default();
                     }
                 }
-                state.set(AppState::Shop).unwrap();
+                if replaying || final_round {
+                    state.set(AppState::Menu).unwrap();
+                } else if campaign {
+                    state.set(AppState::Map).unwrap();
+                } else {
+                    state.set(AppState::Shop).unwrap();
+                }
             }
         }
     }
 
     if let Some(next_action) = next_action {
         battle.action = next_action;
+        if let Some(playback) = &mut playback {
+            playback.action_finished();
+        }
+    }
+}
+
+/// None while both teams still have someone standing
+fn battle_result(left_alive: bool, right_alive: bool) -> Option<BattleResult> {
+    match (left_alive, right_alive) {
+        (true, true) => None,
+        (true, false) => Some(BattleResult::Win),
+        (false, true) => Some(BattleResult::Lose),
+        (false, false) => Some(BattleResult::Tie),
+    }
+}
+
+/// pays out the reward for a finished battle and starts showing its message
+fn end_battle(
+    rules: &Rules,
+    result: BattleResult,
+    boss: Option<Boss>,
+    replaying: bool,
+    wins_trophies: bool,
+    players: &Players,
+    battle_messages: &BattleMessages,
+    trophies: &mut Trophies,
+    coins: &mut Coins,
+    battle_ended_events: &mut EventWriter<BattleEnded>,
+) -> Action {
+    let reward = sim::reward(rules, result, boss);
+    if !replaying {
+        // every player is paid for how the battle went for their own team
+        for player in players.all() {
+            let player_result = player.result(result);
+            if wins_trophies && player_result == BattleResult::Win {
+                trophies.won[*player] += 1;
+            }
+            coins[*player] += sim::reward(rules, player_result, boss);
+        }
+    }
+    battle_ended_events.send(BattleEnded { result, reward });
+    let entity = match result {
+        BattleResult::Win => battle_messages.you_win,
+        BattleResult::Lose => battle_messages.you_lose,
+        BattleResult::Tie => battle_messages.you_tie,
+    };
+    Action::ShowMessage {
+        entity,
+        animate_in: AnimateRange::new(Duration::from_secs_f32(1.0), Ease::OutBack, 0.0..1.0, false),
+        animate_out: AnimateRange::new(
+            Duration::from_secs_f32(0.6),
+            Ease::InOutCirc,
+            1.0..0.0,
+            false,
+        ),
+    }
+}
+
+/// What the battle needs to know about a buddy besides its stats
+type TraitQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static BuddyColor,
+        Option<&'static BossAbility>,
+        &'static BuddyName,
+    ),
+>;
+
+/// the damage an attack deals, and whether it was effective
+fn attack_damage(
+    rules: &Rules,
+    traits: &TraitQuery,
+    attacker: Entity,
+    target: Entity,
+    strength: usize,
+) -> (usize, bool) {
+    match (traits.get(attacker), traits.get(target)) {
+        (Ok((attacker_color, ..)), Ok((target_color, ..))) => {
+            sim::attack_damage(rules, *attacker_color, *target_color, strength)
+        }
+        _ => (strength, false),
+    }
+}
+
+fn thorns(traits: &TraitQuery, target: Entity) -> usize {
+    sim::thorns_damage(ability(traits, target))
+}
+
+fn regenerate(traits: &TraitQuery, buddy: Entity, health: &mut Health) {
+    let amount = sim::regeneration(ability(traits, buddy), health.0.value(), health.0.base());
+    if amount > 0 {
+        health.0.add(amount);
+    }
+}
+
+fn ability(traits: &TraitQuery, buddy: Entity) -> Option<BossAbility> {
+    traits
+        .get(buddy)
+        .ok()
+        .and_then(|(_, ability, _)| ability.copied())
+}
+
+fn label(traits: &TraitQuery, buddy: Entity) -> Option<BuddyLabel> {
+    traits.get(buddy).ok().map(|(color, _, name)| BuddyLabel {
+        name: name.clone(),
+        color: *color,
+    })
+}
+
+/// the damage that gets through the target's shield, if it has one
+fn shielded_damage(shields: &mut Query<&mut Shield>, target: Entity, damage: usize) -> usize {
+    match shields.get_mut(target) {
+        Ok(mut shield) => shield.absorb(damage),
+        Err(_) => damage,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{
+        buddy::{Attribute, Slot},
+        events::EventsPlugin,
+        synergy::SynergyPlugin,
+    };
+    use bevy::ecs::event::Events;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    /// Fights a battle with the real battle and synergy systems, 50ms of battle time a frame. Returns
+    /// the result and how many exchanges it took.
+    fn fight_in_app(
+        rules: &Rules,
+        left: &[(BuddyTemplate, Option<BossAbility>)],
+        right: &[(BuddyTemplate, Option<BossAbility>)],
+    ) -> (BattleResult, usize) {
+        let mut app = App::new();
+        app.add_plugin(EventsPlugin)
+            .add_plugin(SynergyPlugin)
+            .add_state(AppState::Battle)
+            .insert_resource(rules.clone())
+            .insert_resource(Battle {
+                action: Action::Begin {
+                    timer: Timer::from_seconds(2.0, false),
+                },
+                ..Default::default()
+            })
+            .init_resource::<Trophies>()
+            .init_resource::<Coins>()
+            .init_resource::<Players>()
+            .insert_resource(BattleClock {
+                delta: Duration::from_millis(50),
+            })
+            .add_system_set(SystemSet::on_update(AppState::Battle).with_system(battle));
+
+        let [you_win, you_lose, you_tie] = [(); 3].map(|_| {
+            app.world
+                .spawn()
+                .insert_bundle((Visibility::default(), Transform::default()))
+                .id()
+        });
+        app.insert_resource(BattleMessages {
+            you_win,
+            you_lose,
+            you_tie,
+        });
+        for (side, team) in [(Side::Left, left), (Side::Right, right)] {
+            for (slot, (template, ability)) in team.iter().enumerate() {
+                let mut buddy = app.world.spawn();
+                buddy.insert_bundle((
+                    Buddy::default(),
+                    Health(Attribute::new(template.health())),
+                    Strength(Attribute::new(template.strength())),
+                    template.color(),
+                    BuddyName(format!("Buddy {}", slot)),
+                    Transform::default(),
+                    Offset::default(),
+                    side,
+                    Slot::new(slot),
+                ));
+                if let Some(ability) = ability {
+                    buddy.insert(*ability);
+                }
+            }
+        }
+
+        let mut attacks = app
+            .world
+            .get_resource::<Events<AttackResolved>>()
+            .unwrap()
+            .get_reader();
+        let mut endings = app
+            .world
+            .get_resource::<Events<BattleEnded>>()
+            .unwrap()
+            .get_reader();
+        let mut attack_count = 0;
+        for _ in 0..100_000 {
+            app.update();
+
+            let events = app.world.get_resource::<Events<AttackResolved>>().unwrap();
+            attack_count += attacks.iter(events).count();
+            let events = app.world.get_resource::<Events<BattleEnded>>().unwrap();
+            if let Some(ended) = endings.iter(events).next() {
+                // both front buddies attack in every exchange
+                return (ended.result, attack_count / 2);
+            }
+        }
+        panic!("the battle never ended");
+    }
+
+    #[test]
+    fn the_sim_fights_like_the_battle_screen() {
+        let mut rng = StdRng::seed_from_u64(42);
+        for battle in 0..40 {
+            let rules = Rules {
+                color_advantage: battle % 2 == 0,
+                ..Default::default()
+            };
+            let round = 1 + battle % 6;
+            let team = |rng: &mut StdRng| {
+                (0..rng.gen_range(0..=Slot::MAX_PER_SIDE))
+                    .map(|_| {
+                        let ability = match rng.gen_range(0..6) {
+                            0 => Some(BossAbility::Thorns),
+                            1 => Some(BossAbility::Regenerate),
+                            _ => None,
+                        };
+                        (BuddyTemplate::random_for_round(rng, round), ability)
+                    })
+                    .collect::<Vec<_>>()
+            };
+            let left = team(&mut rng);
+            let right = team(&mut rng);
+
+            let outcome = sim::fight(&rules, &left, &right);
+            // regenerating buddies can stall for the sim's whole exchange limit, which would
+            // take far too long to animate
+            if outcome.exchanges > 50 {
+                continue;
+            }
+            assert_eq!(
+                fight_in_app(&rules, &left, &right),
+                (outcome.result, outcome.exchanges),
+                "battle {}",
+                battle
+            );
+        }
     }
 }
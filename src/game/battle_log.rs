@@ -0,0 +1,343 @@
+use crate::{
+    game::{
+        buddy::{Health, Strength},
+        events::{
+            AttackResolved, BattleEnded, BattleResult, BuddyFainted, BuddyLabel, BuddyMovedUp,
+        },
+        hot_seat::{Player, Players},
+        persist,
+    },
+    loading::GameAssets,
+    menu::{HOVERED_BUTTON, NORMAL_BUTTON},
+    AppState,
+};
+use bevy::{input::mouse::MouseWheel, prelude::*};
+
+/// Narrates the battle as it happens, so it's clear why a battle went the way it did
+pub struct BattleLogPlugin;
+
+impl Plugin for BattleLogPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BattleLog>()
+            .add_system_set(
+                SystemSet::on_enter(AppState::Battle).with_system(spawn_battle_log_panel),
+            )
+            .add_system_set(
+                SystemSet::on_update(AppState::Battle)
+                    .with_system(record_battle_log)
+                    .with_system(battle_log_controls)
+                    .with_system(show_battle_log),
+            )
+            .add_system_set(SystemSet::on_exit(AppState::Battle).with_system(despawn_battle_log));
+    }
+}
+
+/// Every line narrated during the current battle
+#[derive(Default)]
+pub struct BattleLog {
+    lines: Vec<String>,
+    /// how many lines up from the newest one the panel is scrolled
+    scroll: usize,
+    collapsed: bool,
+}
+
+impl BattleLog {
+    const VISIBLE_LINES: usize = 10;
+    const EXPORT_FILE: &'static str = "battle_log.txt";
+
+    fn push(&mut self, line: String) {
+        self.lines.push(line);
+        // new lines bring the panel back to the bottom
+        self.scroll = 0;
+    }
+
+    fn max_scroll(&self) -> usize {
+        self.lines.len().saturating_sub(Self::VISIBLE_LINES)
+    }
+
+    fn visible(&self) -> String {
+        let end = self.lines.len() - self.scroll.min(self.max_scroll());
+        let start = end.saturating_sub(Self::VISIBLE_LINES);
+        self.lines[start..end].join("\n")
+    }
+}
+
+#[derive(Component, Copy, Clone)]
+enum BattleLogButton {
+    Toggle,
+    Export,
+}
+
+#[derive(Component)]
+struct BattleLogText;
+
+#[derive(Component)]
+struct ToggleLabel;
+
+struct BattleLogPanel(Entity);
+
+const TEXT_COLOR: Color = Color::rgb(0.2, 0.2, 0.2);
+
+fn spawn_battle_log_panel(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    mut log: ResMut<BattleLog>,
+) {
+    log.lines.clear();
+    log.scroll = 0;
+
+    let font = assets.title_font.clone();
+    // its own ui root, so leaving a replay can't despawn it out from under us
+    let panel = commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Px(460.0), Val::Auto),
+                position_type: PositionType::Absolute,
+                // ui space is y-up, so this sits in the top right corner
+                position: Rect {
+                    bottom: Val::Px(20.0),
+                    right: Val::Px(20.0),
+                    ..Default::default()
+                },
+                padding: Rect::all(Val::Px(8.0)),
+                flex_direction: FlexDirection::ColumnReverse,
+                align_items: AlignItems::FlexStart,
+                ..Default::default()
+            },
+            color: Color::rgba(0.95, 0.95, 1.0, 0.8).into(),
+            ..Default::default()
+        })
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Row,
+                        align_items: AlignItems::Center,
+                        ..Default::default()
+                    },
+                    color: Color::NONE.into(),
+                    ..Default::default()
+                })
+                .with_children(|parent| {
+                    parent.spawn_bundle(TextBundle {
+                        style: Style {
+                            margin: Rect {
+                                right: Val::Px(12.0),
+                                ..Default::default()
+                            },
+                            ..Default::default()
+                        },
+                        text: Text::with_section(
+                            "Battle Log",
+                            TextStyle {
+                                font: font.clone(),
+                                font_size: 34.0,
+                                color: TEXT_COLOR,
+                            },
+                            Default::default(),
+                        ),
+                        ..Default::default()
+                    });
+                    let toggle = if log.collapsed { "Show" } else { "Hide" };
+                    spawn_log_button(parent, &font, toggle, BattleLogButton::Toggle);
+                    spawn_log_button(parent, &font, "Export", BattleLogButton::Export);
+                });
+            parent
+                .spawn_bundle(TextBundle {
+                    text: Text::with_section(
+                        "",
+                        TextStyle {
+                            font: font.clone(),
+                            font_size: 26.0,
+                            color: TEXT_COLOR,
+                        },
+                        Default::default(),
+                    ),
+                    ..Default::default()
+                })
+                .insert(BattleLogText);
+        })
+        .id();
+    commands.insert_resource(BattleLogPanel(panel));
+}
+
+fn spawn_log_button(
+    parent: &mut ChildBuilder,
+    font: &Handle<Font>,
+    label: &str,
+    button: BattleLogButton,
+) {
+    parent
+        .spawn_bundle(ButtonBundle {
+            style: Style {
+                size: Size::new(Val::Px(90.0), Val::Px(36.0)),
+                margin: Rect::all(Val::Px(4.0)),
+                // horizontally center child text
+                justify_content: JustifyContent::Center,
+                // vertically center child text
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            color: NORMAL_BUTTON.into(),
+            ..Default::default()
+        })
+        .insert(button)
+        .with_children(|parent| {
+            let mut text = parent.spawn_bundle(TextBundle {
+                text: Text::with_section(
+                    label,
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: 28.0,
+                        color: Color::rgb(0.9, 0.9, 0.9),
+                    },
+                    Default::default(),
+                ),
+                ..Default::default()
+            });
+            if let BattleLogButton::Toggle = button {
+                text.insert(ToggleLabel);
+            }
+        });
+}
+
+fn describe(label: &BuddyLabel) -> String {
+    format!("{:?} {}", label.color, label.name.0)
+}
+
+fn record_battle_log(
+    mut log: ResMut<BattleLog>,
+    mut attack_events: EventReader<AttackResolved>,
+    mut fainted_events: EventReader<BuddyFainted>,
+    mut moved_up_events: EventReader<BuddyMovedUp>,
+    mut battle_ended_events: EventReader<BattleEnded>,
+    players: Res<Players>,
+    buddies: Query<(&Health, &Strength)>,
+) {
+    for attack in attack_events.iter() {
+        let stats = match buddies.get(attack.attacker) {
+            Ok((health, strength)) => {
+                format!(" ({}/{})", health.0.value(), strength.0.value())
+            }
+            Err(_) => String::new(),
+        };
+        let effective = if attack.effective {
+            ", super effective!"
+        } else {
+            ""
+        };
+        log.push(format!(
+            "{}{} hits {} for {}{}",
+            describe(&attack.attacker_label),
+            stats,
+            describe(&attack.target_label),
+            attack.damage,
+            effective
+        ));
+    }
+    for fainted in fainted_events.iter() {
+        log.push(format!("{} faints", describe(&fainted.label)));
+    }
+    for moved_up in moved_up_events.iter() {
+        let line = if moved_up.slot == 0 {
+            format!("{} steps up to the front", describe(&moved_up.label))
+        } else {
+            format!("{} moves up", describe(&moved_up.label))
+        };
+        log.push(line);
+    }
+    for ended in battle_ended_events.iter() {
+        if players.owns_both_teams() {
+            let winner = Player::ALL
+                .iter()
+                .find(|player| player.result(ended.result) == BattleResult::Win);
+            log.push(match winner {
+                // nobody is playing against anyone in the sandbox
+                Some(winner) if players.sandbox => format!("{:?} team wins", winner.side()),
+                Some(winner) => format!("{} wins", winner.name()),
+                None => "It's a tie".to_string(),
+            });
+            continue;
+        }
+        let result = match ended.result {
+            BattleResult::Win => "Your team wins",
+            BattleResult::Lose => "Your team loses",
+            BattleResult::Tie => "It's a tie",
+        };
+        log.push(format!("{}: +{} coins", result, ended.reward));
+    }
+}
+
+fn battle_log_controls(
+    mut log: ResMut<BattleLog>,
+    keyboard: Res<Input<KeyCode>>,
+    mut wheel_events: EventReader<MouseWheel>,
+    mut interactions: Query<
+        (&Interaction, &BattleLogButton, &mut UiColor),
+        (Changed<Interaction>, With<Button>),
+    >,
+) {
+    let mut toggle = keyboard.just_pressed(KeyCode::L);
+    let mut export = false;
+    for (interaction, button, mut color) in interactions.iter_mut() {
+        match *interaction {
+            Interaction::Clicked => match button {
+                BattleLogButton::Toggle => toggle = true,
+                BattleLogButton::Export => export = true,
+            },
+            Interaction::Hovered => *color = HOVERED_BUTTON.into(),
+            Interaction::None => *color = NORMAL_BUTTON.into(),
+        }
+    }
+
+    if toggle {
+        log.collapsed = !log.collapsed;
+    }
+    if export {
+        let contents = log.lines.join("\n");
+        if let Some(path) = persist::export_text(BattleLog::EXPORT_FILE, &contents) {
+            log.push(format!("Saved to {}", path.display()));
+        }
+    }
+
+    let mut scroll = 0isize;
+    for wheel in wheel_events.iter() {
+        scroll += wheel.y.signum() as isize;
+    }
+    if keyboard.just_pressed(KeyCode::PageUp) {
+        scroll += BattleLog::VISIBLE_LINES as isize;
+    }
+    if keyboard.just_pressed(KeyCode::PageDown) {
+        scroll -= BattleLog::VISIBLE_LINES as isize;
+    }
+    if scroll != 0 {
+        let max_scroll = log.max_scroll() as isize;
+        log.scroll = (log.scroll as isize + scroll).clamp(0, max_scroll) as usize;
+    }
+}
+
+fn show_battle_log(
+    log: Res<BattleLog>,
+    mut texts: Query<&mut Text, With<BattleLogText>>,
+    mut toggle_labels: Query<&mut Text, (With<ToggleLabel>, Without<BattleLogText>)>,
+) {
+    if !log.is_changed() {
+        return;
+    }
+    let value = if log.collapsed {
+        String::new()
+    } else {
+        log.visible()
+    };
+    for mut text in texts.iter_mut() {
+        text.sections[0].value = value.clone();
+    }
+    for mut text in toggle_labels.iter_mut() {
+        text.sections[0].value = if log.collapsed { "Show" } else { "Hide" }.to_string();
+    }
+}
+
+fn despawn_battle_log(mut commands: Commands, panel: Res<BattleLogPanel>) {
+    commands.entity(panel.0).despawn_recursive();
+    commands.remove_resource::<BattleLogPanel>();
+}
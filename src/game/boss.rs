@@ -0,0 +1,181 @@
+use crate::{
+    game::{
+        battle::Battle,
+        buddy::{BuddyColor, BuddyTemplate},
+        Z_MESSAGE,
+    },
+    loading::GameAssets,
+    AppState,
+};
+use bevy::prelude::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::f32::consts::PI;
+
+pub struct BossPlugin;
+
+impl Plugin for BossPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_update(AppState::Battle).with_system(boss_intro))
+            .add_system_set(SystemSet::on_exit(AppState::Battle).with_system(cleanup_boss_intro));
+    }
+}
+
+/// Every few rounds the opponent is one of these instead of a random team
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Boss {
+    /// one huge buddy with thorns
+    Giant,
+    /// three blue buddies that regenerate
+    BlueCrew,
+}
+
+impl Boss {
+    const EVERY_ROUNDS: usize = 5;
+    const ALL: &'static [Boss] = &[Boss::Giant, Boss::BlueCrew];
+
+    pub fn for_round(round: usize) -> Option<Boss> {
+        if round == 0 || !round.is_multiple_of(Self::EVERY_ROUNDS) {
+            return None;
+        }
+        let index = (round / Self::EVERY_ROUNDS - 1) % Self::ALL.len();
+        Some(Self::ALL[index])
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            Boss::Giant => "Big Bertha",
+            Boss::BlueCrew => "The Blue Crew",
+        }
+    }
+
+    /// extra coins for beating the boss, on top of the normal reward
+    pub fn bonus_reward(&self) -> usize {
+        match self {
+            Boss::Giant => 6,
+            Boss::BlueCrew => 5,
+        }
+    }
+
+    /// the boss team, front to back
+    pub fn team(&self, rng: &mut impl Rng, round: usize) -> Vec<(BuddyTemplate, BossAbility)> {
+        match self {
+            Boss::Giant => vec![(
+                BuddyTemplate::random_for_round(rng, round)
+                    .with_name(self.title())
+                    .with_stats(8 + round, 2 + round / 2)
+                    .with_size(1.6),
+                BossAbility::Thorns,
+            )],
+            Boss::BlueCrew => (0..3)
+                .map(|_| {
+                    (
+                        BuddyTemplate::random_for_round(rng, round)
+                            .with_color(BuddyColor::Blue)
+                            .with_stats(3 + round / 2, 2 + round / 3)
+                            .with_size(1.15),
+                        BossAbility::Regenerate,
+                    )
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Component, Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BossAbility {
+    /// anything that hits this buddy takes some damage back
+    Thorns,
+    /// heals a little after every exchange it survives
+    Regenerate,
+}
+
+impl BossAbility {
+    pub const THORNS_DAMAGE: usize = 1;
+    pub const REGENERATE_HEALTH: usize = 1;
+}
+
+#[derive(Component)]
+pub struct BossIntro;
+
+pub fn spawn_boss_intro(commands: &mut Commands, assets: &GameAssets, boss: Boss) {
+    commands
+        .spawn_bundle(Text2dBundle {
+            text: Text::with_section(
+                format!("Boss: {}", boss.title()),
+                TextStyle {
+                    font: assets.title_font.clone(),
+                    font_size: 120.0,
+                    color: Color::rgb(0.6, 0.15, 0.15),
+                },
+                TextAlignment {
+                    vertical: VerticalAlign::Center,
+                    horizontal: HorizontalAlign::Center,
+                },
+            ),
+            transform: Transform::from_xyz(0.0, 220.0, Z_MESSAGE).with_scale(Vec3::ZERO),
+            ..Default::default()
+        })
+        .insert(BossIntro);
+}
+
+/// the boss title swells in and out while the battle begins
+fn boss_intro(battle: Res<Battle>, mut intros: Query<&mut Transform, With<BossIntro>>) {
+    let scale = battle
+        .beginning()
+        .map_or(0.0, |percent| (percent * PI).sin().min(0.8) / 0.8);
+    for mut transform in intros.iter_mut() {
+        transform.scale = Vec3::new(scale, scale, 1.0);
+    }
+}
+
+fn cleanup_boss_intro(mut commands: Commands, intros: Query<Entity, With<BossIntro>>) {
+    for entity in intros.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn bosses_turn_up_every_few_rounds_in_turn() {
+        let bosses = (0..=21).map(Boss::for_round).collect::<Vec<_>>();
+        for (round, boss) in bosses.iter().enumerate() {
+            match round {
+                5 | 15 => assert_eq!(*boss, Some(Boss::Giant), "round {}", round),
+                10 | 20 => assert_eq!(*boss, Some(Boss::BlueCrew), "round {}", round),
+                _ => assert_eq!(*boss, None, "round {}", round),
+            }
+        }
+    }
+
+    #[test]
+    fn boss_teams_match_their_descriptions() {
+        let mut rng = StdRng::seed_from_u64(38);
+
+        let giant = Boss::Giant.team(&mut rng, 5);
+        assert_eq!(giant.len(), 1);
+        assert_eq!(giant[0].1, BossAbility::Thorns);
+
+        let crew = Boss::BlueCrew.team(&mut rng, 10);
+        assert_eq!(crew.len(), 3);
+        for (template, ability) in crew.iter() {
+            assert_eq!(template.color(), BuddyColor::Blue);
+            assert_eq!(*ability, BossAbility::Regenerate);
+        }
+    }
+
+    #[test]
+    fn later_bosses_are_tougher() {
+        let mut rng = StdRng::seed_from_u64(38);
+        for boss in Boss::ALL {
+            let early = boss.team(&mut rng, 5);
+            let late = boss.team(&mut rng, 15);
+            assert!(late[0].0.health() > early[0].0.health(), "{:?}", boss);
+            assert!(late[0].0.strength() > early[0].0.strength(), "{:?}", boss);
+        }
+    }
+}
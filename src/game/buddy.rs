@@ -1,13 +1,19 @@
 use crate::{
     game::{
         animate::{AnimateRange, AnimateScale, Ease},
+        appearance::Appearance,
+        emotion::{Emotion, Reaction},
+        name::BuddyName,
         shop::BuddyDragState,
+        workshop::Upgrades,
         Z_BUDDY,
     },
+    loading::GameAssets,
     AppState,
 };
-use bevy::{prelude::*, text::Text2dSize};
+use bevy::{prelude::*, text::Text2dSize, transform::TransformSystem};
 use rand::Rng;
+use serde::{Deserialize, Serialize};
 use std::{f32::consts::PI, time::Duration};
 
 pub struct BuddyPlugin;
@@ -16,42 +22,37 @@ impl Plugin for BuddyPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<OutlineTimer>()
             .add_system(update_outlines)
-            .add_system(set_buddy_face)
             .add_system(wobble_buddy)
-            .add_system(set_health_counter)
-            .add_system(set_strength_counter)
-            .add_system_set(add_buddy_render_systems_to_set(SystemSet::new()))
-            .add_system_set(add_buddy_render_systems_to_set(SystemSet::on_update(
-                AppState::Shop,
-            )))
-            .add_system_set(add_buddy_render_systems_to_set(SystemSet::on_update(
-                AppState::Battle,
-            )));
-    }
-}
-
-// This is lame, but we need to duplicate "buddy rendering" systems
-// to ensure they run at the appropriate time on "same frame transitions"
-// without doing this for each relevant AppState, spawned buddies
-// will render as "white faceless monsters" at the center of the screen
-// dont put systems that tick in here or they might be double-ticked on a frame
-pub fn add_buddy_render_systems_to_set(set: SystemSet) -> SystemSet {
-    set.with_system(set_health_counter)
-        .with_system(set_strength_counter)
-        .with_system(move_buddy)
-        .with_system(set_buddy_color)
-}
-
-#[derive(Component, PartialEq, Eq)]
+            // buddies are spawned by state transition systems in CoreStage::Update, so these run
+            // afterwards in PostUpdate to make sure new buddies never render a frame at the center
+            // of the screen (or as "white faceless monsters")
+            .add_system_set_to_stage(
+                CoreStage::PostUpdate,
+                SystemSet::new()
+                    .with_system(move_buddy.before(TransformSystem::TransformPropagate))
+                    .with_system(set_buddy_face)
+                    .with_system(set_buddy_color)
+                    .with_system(set_health_counter)
+                    .with_system(set_strength_counter),
+            );
+    }
+}
+
+#[derive(Component, Debug, Default, Copy, Clone, PartialEq, Eq)]
 pub enum Side {
+    #[default]
     Left,
     Right,
     Shop,
 }
 
-impl Default for Side {
-    fn default() -> Self {
-        Self::Left
+impl Side {
+    /// the right team (and the shop) face the left team
+    pub fn faces_left(&self) -> bool {
+        match self {
+            Side::Left => false,
+            Side::Right | Side::Shop => true,
+        }
     }
 }
 
@@ -81,39 +82,146 @@ impl Default for Slot {
     }
 }
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct BuddyTemplate {
+    // runs saved before buddies had names get a fresh one
+    #[serde(default)]
+    name: BuddyName,
     face: BuddyFace,
     health: usize,
     strength: usize,
     color: BuddyColor,
+    // runs saved before buddies had appearances get a new look
+    #[serde(default = "Appearance::random")]
+    appearance: Appearance,
+    #[serde(default)]
+    upgrades: Upgrades,
+    /// bosses are bigger than everyone else
+    #[serde(default = "BuddyTemplate::default_size")]
+    size: f32,
 }
 
 impl BuddyTemplate {
-    pub fn random_for_round(round: usize) -> Self {
-        let mut rng = rand::thread_rng();
+    pub fn from_buddy(
+        name: &BuddyName,
+        face: &BuddyFace,
+        color: &BuddyColor,
+        appearance: &Appearance,
+        upgrades: &Upgrades,
+        health: &Health,
+        strength: &Strength,
+    ) -> Self {
+        Self {
+            name: name.clone(),
+            face: *face,
+            health: health.0.base(),
+            strength: strength.0.base(),
+            color: *color,
+            appearance: *appearance,
+            upgrades: *upgrades,
+            size: Self::default_size(),
+        }
+    }
+
+    pub fn random_for_round(rng: &mut impl Rng, round: usize) -> Self {
         let strength = rng.gen_range(1..(2 + round));
         let health = rng.gen_range(1..(2 + round));
         Self {
-            face: BuddyFace::random(),
+            name: BuddyName::generate(rng),
+            face: BuddyFace::random(rng),
             health,
             strength,
-            color: BuddyColor::random(),
+            color: BuddyColor::random(rng),
+            appearance: Appearance::from_rng(rng),
+            upgrades: Upgrades::default(),
+            size: Self::default_size(),
         }
     }
+
+    fn default_size() -> f32 {
+        1.0
+    }
+
+    pub fn health(&self) -> usize {
+        self.health
+    }
+
+    pub fn strength(&self) -> usize {
+        self.strength
+    }
+
+    pub fn color(&self) -> BuddyColor {
+        self.color
+    }
+
+    pub fn face(&self) -> BuddyFace {
+        self.face
+    }
+
+    pub fn upgrades(&self) -> Upgrades {
+        self.upgrades
+    }
+
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.name = BuddyName(name.to_string());
+        self
+    }
+
+    pub fn with_stats(mut self, health: usize, strength: usize) -> Self {
+        self.health = health;
+        self.strength = strength;
+        self
+    }
+
+    pub fn with_color(mut self, color: BuddyColor) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn with_face(mut self, face: BuddyFace) -> Self {
+        self.face = face;
+        self
+    }
+
+    pub fn with_appearance(mut self, appearance: Appearance) -> Self {
+        self.appearance = appearance;
+        self
+    }
+
+    pub fn with_upgrades(mut self, upgrades: Upgrades) -> Self {
+        self.upgrades = upgrades;
+        self
+    }
+
+    pub fn with_size(mut self, size: f32) -> Self {
+        self.size = size;
+        self
+    }
     // pub fn base_cost(&self) -> usize {
     //     self.health + self.strength
     // }
     pub fn spawn(
         self,
         commands: &mut Commands,
-        asset_server: &AssetServer,
+        assets: &GameAssets,
         slot: usize,
         side: Side,
         transform: Transform,
     ) -> Entity {
-        commands
+        let mut body = None;
+        let mut face = None;
+        let mut health_counter = None;
+        let mut strength_counter = None;
+        let mut accessory_sprite = None;
+        let appearance = self.appearance;
+        let size = self.size;
+        let tint = appearance.tint(&self.color);
+        let root = commands
             .spawn_bundle(BuddyBundle {
+                name: self.name,
                 color: self.color,
+                appearance,
+                upgrades: self.upgrades,
                 face: self.face,
                 health: Health(Attribute::new(self.health)),
                 strength: Strength(Attribute::new(self.strength)),
@@ -133,95 +241,170 @@ impl BuddyTemplate {
                         false,
                     ))
                     .with_children(|parent| {
+                        // the appearance scale lives on its own entity, so the outline can keep
+                        // spinning in place without turning a tall buddy's outline wide
                         parent
-                            .spawn_bundle(SpriteBundle {
-                                texture: asset_server.load("buddy/base.png"),
-                                transform: Transform::from_xyz(0.0, 0.0, Z_BUDDY)
-                                    .with_scale(Vec3::splat(0.5)),
-                                ..Default::default()
-                            })
-                            .insert(BuddyBodySprite);
-                        parent
-                            .spawn_bundle(SpriteBundle {
-                                texture: asset_server.load("buddy/outline.png"),
-                                transform: Transform::from_xyz(0.0, 0.0, Z_BUDDY + 0.1)
-                                    .with_scale(Vec3::splat(0.5)),
-                                ..Default::default()
-                            })
-                            .insert(BuddyOutline);
-                        parent
-                            .spawn_bundle(SpriteBundle {
-                                transform: Transform::from_xyz(0.0, 0.0, Z_BUDDY + 0.2)
-                                    .with_scale(Vec3::splat(0.5)),
-                                ..Default::default()
-                            })
-                            .insert(BuddyFaceSprite);
+                            .spawn_bundle((
+                                Transform::from_scale(
+                                    appearance.scale() * Vec3::new(size, size, 1.0),
+                                ),
+                                GlobalTransform::default(),
+                            ))
+                            .with_children(|parent| {
+                                body = Some(
+                                    parent
+                                        .spawn_bundle(SpriteBundle {
+                                            sprite: Sprite {
+                                                color: tint,
+                                                ..Default::default()
+                                            },
+                                            texture: assets.buddy_base.clone(),
+                                            transform: Transform::from_xyz(0.0, 0.0, Z_BUDDY)
+                                                .with_scale(Vec3::splat(0.5)),
+                                            ..Default::default()
+                                        })
+                                        .insert(BuddyBodySprite)
+                                        .id(),
+                                );
+                                parent
+                                    .spawn_bundle(SpriteBundle {
+                                        texture: assets.buddy_outline.clone(),
+                                        transform: Transform::from_xyz(0.0, 0.0, Z_BUDDY + 0.1)
+                                            .with_scale(Vec3::splat(0.5)),
+                                        ..Default::default()
+                                    })
+                                    .insert(BuddyOutline);
+                                face = Some(
+                                    parent
+                                        .spawn_bundle(SpriteBundle {
+                                            sprite: Sprite {
+                                                flip_x: side.faces_left(),
+                                                ..Default::default()
+                                            },
+                                            texture: self.face.get_image(assets),
+                                            transform: Transform::from_xyz(0.0, 0.0, Z_BUDDY + 0.2)
+                                                .with_scale(Vec3::splat(0.5)),
+                                            ..Default::default()
+                                        })
+                                        .insert(BuddyFaceSprite)
+                                        .id(),
+                                );
+                                if let Some(accessory) = appearance.accessory() {
+                                    accessory_sprite = Some(
+                                        parent
+                                            .spawn_bundle(SpriteBundle {
+                                                sprite: Sprite {
+                                                    color: if accessory.is_tinted() {
+                                                        tint
+                                                    } else {
+                                                        Color::WHITE
+                                                    },
+                                                    ..Default::default()
+                                                },
+                                                texture: accessory.get_image(assets),
+                                                transform: Transform::from_xyz(
+                                                    0.0,
+                                                    0.0,
+                                                    Z_BUDDY + accessory.z_offset(),
+                                                )
+                                                .with_scale(Vec3::splat(0.5)),
+                                                ..Default::default()
+                                            })
+                                            .id(),
+                                    );
+                                }
+                            });
                     });
                 parent
                     .spawn_bundle(SpriteBundle {
                         transform: Transform::from_xyz(-40.0, -70.0, Z_BUDDY + 0.3)
                             .with_scale(Vec3::splat(0.5)),
-                        texture: asset_server.load("buddy/health.png"),
+                        texture: assets.buddy_health.clone(),
                         ..Default::default()
                     })
                     .with_children(|parent| {
-                        parent
-                            .spawn_bundle(Text2dBundle {
-                                text: Text::with_section(
-                                    "0",
-                                    TextStyle {
-                                        font: asset_server.load("font/CaveatBrush-Regular.ttf"),
-                                        font_size: 110.0,
-                                        color: Color::hex("ececec").unwrap(),
+                        health_counter = Some(
+                            parent
+                                .spawn_bundle(Text2dBundle {
+                                    text: Text::with_section(
+                                        self.health.to_string(),
+                                        TextStyle {
+                                            font: assets.number_font.clone(),
+                                            font_size: 110.0,
+                                            color: Color::hex("ececec").unwrap(),
+                                        },
+                                        TextAlignment {
+                                            vertical: VerticalAlign::Bottom,
+                                            horizontal: HorizontalAlign::Left,
+                                        },
+                                    ),
+                                    text_2d_size: Text2dSize {
+                                        size: Size::new(100., 100.),
                                     },
-                                    TextAlignment {
-                                        vertical: VerticalAlign::Bottom,
-                                        horizontal: HorizontalAlign::Left,
-                                    },
-                                ),
-                                text_2d_size: Text2dSize {
-                                    size: Size::new(100., 100.),
-                                },
-                                transform: Transform::from_xyz(-20.0, -55.0, 0.1),
-                                ..Default::default()
-                            })
-                            .insert(HealthCounter);
+                                    transform: Transform::from_xyz(-20.0, -55.0, 0.1),
+                                    ..Default::default()
+                                })
+                                .insert(HealthCounter)
+                                .id(),
+                        );
                     });
                 parent
                     .spawn_bundle(SpriteBundle {
                         transform: Transform::from_xyz(40.0, -70.0, Z_BUDDY + 0.3)
                             .with_scale(Vec3::splat(0.5)),
-                        texture: asset_server.load("buddy/strength.png"),
+                        texture: assets.buddy_strength.clone(),
                         ..Default::default()
                     })
                     .with_children(|parent| {
-                        parent
-                            .spawn_bundle(Text2dBundle {
-                                text: Text::with_section(
-                                    "0",
-                                    TextStyle {
-                                        font: asset_server.load("font/CaveatBrush-Regular.ttf"),
-                                        font_size: 110.0,
-                                        color: Color::hex("ececec").unwrap(),
+                        strength_counter = Some(
+                            parent
+                                .spawn_bundle(Text2dBundle {
+                                    text: Text::with_section(
+                                        self.strength.to_string(),
+                                        TextStyle {
+                                            font: assets.number_font.clone(),
+                                            font_size: 110.0,
+                                            color: Color::hex("ececec").unwrap(),
+                                        },
+                                        TextAlignment {
+                                            vertical: VerticalAlign::Bottom,
+                                            horizontal: HorizontalAlign::Left,
+                                        },
+                                    ),
+                                    text_2d_size: Text2dSize {
+                                        size: Size::new(100., 100.),
                                     },
-                                    TextAlignment {
-                                        vertical: VerticalAlign::Bottom,
-                                        horizontal: HorizontalAlign::Left,
-                                    },
-                                ),
-                                text_2d_size: Text2dSize {
-                                    size: Size::new(100., 100.),
-                                },
-                                transform: Transform::from_xyz(-10.0, -55.0, 0.1),
-                                ..Default::default()
-                            })
-                            .insert(StrengthCounter);
+                                    transform: Transform::from_xyz(-10.0, -55.0, 0.1),
+                                    ..Default::default()
+                                })
+                                .insert(StrengthCounter)
+                                .id(),
+                        );
                     });
             })
-            .id()
+            .id();
+        commands.entity(root).insert(BuddyParts {
+            body: body.unwrap(),
+            face: face.unwrap(),
+            health_counter: health_counter.unwrap(),
+            strength_counter: strength_counter.unwrap(),
+            accessory: accessory_sprite,
+        });
+        root
     }
 }
 
+/// Direct links to the child entities that display a buddy's state, so visual updates don't
+/// have to walk the hierarchy
+#[derive(Component)]
+pub struct BuddyParts {
+    pub body: Entity,
+    pub face: Entity,
+    pub health_counter: Entity,
+    pub strength_counter: Entity,
+    pub accessory: Option<Entity>,
+}
+
 #[derive(Component)]
 pub struct Buddy {
     pub alive: bool,
@@ -236,22 +419,31 @@ impl Default for Buddy {
 #[derive(Component, Default)]
 pub struct BuddyOutline;
 
-#[derive(Component)]
+#[derive(Component, Default, Copy, Clone, Serialize, Deserialize)]
 pub enum BuddyFace {
+    #[default]
     Happy,
     Neutral,
 }
 
 impl BuddyFace {
-    pub fn get_path(&self) -> &'static str {
+    pub fn get_image(&self, assets: &GameAssets) -> Handle<Image> {
         match self {
-            BuddyFace::Happy => "buddy/face/happy.png",
-            BuddyFace::Neutral => "buddy/face/neutral.png",
+            BuddyFace::Happy => assets.face_happy.clone(),
+            BuddyFace::Neutral => assets.face_neutral.clone(),
         }
     }
 
-    pub fn random() -> BuddyFace {
-        let index = rand::thread_rng().gen_range(0..2);
+    /// the face after this one, for when the workshop swaps it out
+    pub fn next(&self) -> BuddyFace {
+        match self {
+            BuddyFace::Happy => BuddyFace::Neutral,
+            BuddyFace::Neutral => BuddyFace::Happy,
+        }
+    }
+
+    pub fn random(rng: &mut impl Rng) -> BuddyFace {
+        let index = rng.gen_range(0..2);
         match index {
             0 => BuddyFace::Happy,
             1 => BuddyFace::Neutral,
@@ -295,22 +487,44 @@ impl BuddyBlink {
     }
 }
 
-#[derive(Component, Default, Copy, Clone)]
-pub struct BuddyColor(Color);
+#[derive(Component, Debug, Default, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BuddyColor {
+    #[default]
+    Red,
+    Green,
+    Blue,
+}
 
 impl BuddyColor {
-    const RED: BuddyColor = Self(Color::rgb(0.67, 0.53, 0.53));
-    const GREEN: BuddyColor = Self(Color::rgb(0.53, 0.67, 0.53));
-    const BLUE: BuddyColor = Self(Color::rgb(0.53, 0.53, 0.67));
-    const COLORS: &'static [BuddyColor] = &[Self::RED, Self::GREEN, Self::BLUE];
-    pub fn random() -> BuddyColor {
-        Self::COLORS[rand::thread_rng().gen_range(0..Self::COLORS.len())]
+    pub const COLORS: &'static [BuddyColor] = &[Self::Red, Self::Green, Self::Blue];
+
+    /// the hue (in degrees) every buddy of this color is tinted around, see [`Appearance::tint`]
+    pub fn hue(&self) -> f32 {
+        match self {
+            BuddyColor::Red => 0.0,
+            BuddyColor::Green => 120.0,
+            BuddyColor::Blue => 240.0,
+        }
     }
-}
 
-impl Default for BuddyFace {
-    fn default() -> Self {
-        BuddyFace::Happy
+    /// red beats green, green beats blue and blue beats red
+    pub fn beats(&self, other: &BuddyColor) -> bool {
+        matches!(
+            (self, other),
+            (BuddyColor::Red, BuddyColor::Green)
+                | (BuddyColor::Green, BuddyColor::Blue)
+                | (BuddyColor::Blue, BuddyColor::Red)
+        )
+    }
+
+    /// the color after this one, for when the workshop recolors a buddy
+    pub fn next(&self) -> BuddyColor {
+        let index = Self::COLORS.iter().position(|color| color == self).unwrap();
+        Self::COLORS[(index + 1) % Self::COLORS.len()]
+    }
+
+    pub fn random(rng: &mut impl Rng) -> BuddyColor {
+        Self::COLORS[rng.gen_range(0..Self::COLORS.len())]
     }
 }
 
@@ -334,13 +548,18 @@ impl Default for OutlineTimer {
 #[derive(Bundle, Default)]
 pub struct BuddyBundle {
     pub buddy: Buddy,
+    pub name: BuddyName,
     pub health: Health,
     pub strength: Strength,
     pub face: BuddyFace,
     pub blink: BuddyBlink,
+    pub emotion: Emotion,
+    pub reaction: Reaction,
     pub position_offset: Offset,
     pub slot: Slot,
     pub color: BuddyColor,
+    pub appearance: Appearance,
+    pub upgrades: Upgrades,
     pub side: Side,
     pub transform: Transform,
     pub global_transform: GlobalTransform,
@@ -383,9 +602,22 @@ impl Attribute {
         }
     }
 
-    // pub fn set_base(&mut self, base: usize) {
-    //     self.base = base;
-    // }
+    pub fn base(&self) -> usize {
+        self.base
+    }
+
+    /// raises the base, and the current value along with it
+    pub fn add_base(&mut self, amount: usize) {
+        self.base += amount;
+        self.value += amount as isize;
+    }
+
+    /// lowers the base, and the current value along with it
+    pub fn remove_base(&mut self, amount: usize) {
+        let amount = amount.min(self.base);
+        self.base -= amount;
+        self.value -= amount as isize;
+    }
 
     pub fn reset(&mut self) {
         self.value = self.base as isize;
@@ -399,9 +631,9 @@ impl Attribute {
         self.value -= amount as isize;
     }
 
-    // pub fn add(&mut self, amount: usize) {
-    //     self.value += amount as isize;
-    // }
+    pub fn add(&mut self, amount: usize) {
+        self.value += amount as isize;
+    }
 }
 
 fn update_outlines(
@@ -426,46 +658,61 @@ fn update_outlines(
 }
 
 fn set_buddy_color(
-    parents: Query<&Parent>,
-    buddies: Query<&BuddyColor, With<Buddy>>,
-    mut bodies: Query<(&mut Sprite, &Parent), With<BuddyBodySprite>>,
+    buddies: Query<(&BuddyColor, &Appearance, &BuddyParts), Changed<BuddyColor>>,
+    mut sprites: Query<&mut Sprite>,
 ) {
-    for (mut sprite, parent) in bodies.iter_mut() {
-        let buddy_entity = parents.get(parent.0).unwrap().0;
-        if let Ok(color) = buddies.get(buddy_entity) {
-            sprite.color = color.0;
+    for (color, appearance, parts) in buddies.iter() {
+        let tint = appearance.tint(color);
+        if let Ok(mut sprite) = sprites.get_mut(parts.body) {
+            sprite.color = tint;
+        }
+        if let (Some(accessory), Some(entity)) = (appearance.accessory(), parts.accessory) {
+            if let Ok(mut sprite) = sprites.get_mut(entity) {
+                if accessory.is_tinted() {
+                    sprite.color = tint;
+                }
+            }
         }
     }
 }
+
 fn set_buddy_face(
-    asset_server: Res<AssetServer>,
+    assets: Res<GameAssets>,
     time: Res<Time>,
-    parents: Query<&Parent>,
-    mut buddies: Query<(&Side, &BuddyFace, &BuddyColor, &mut BuddyBlink), With<Buddy>>,
-    mut faces: Query<
-        (&mut Handle<Image>, &mut Sprite, &Parent),
-        (With<BuddyFaceSprite>, Without<BuddyBodySprite>),
-    >,
+    mut buddies: Query<(
+        &Side,
+        &BuddyFace,
+        &Emotion,
+        &mut BuddyBlink,
+        &BuddyParts,
+        ChangeTrackers<Side>,
+        ChangeTrackers<BuddyFace>,
+        ChangeTrackers<Emotion>,
+    )>,
+    mut faces: Query<(&mut Handle<Image>, &mut Sprite)>,
 ) {
-    for (mut image, mut sprite, parent) in faces.iter_mut() {
-        let buddy_entity = parents.get(parent.0).unwrap().0;
-        if let Ok((side, face, _, mut blink)) = buddies.get_mut(buddy_entity) {
-            match side {
-                Side::Left => {
-                    sprite.flip_x = false;
-                }
-                Side::Right => {
-                    sprite.flip_x = true;
-                }
-                Side::Shop => {
-                    sprite.flip_x = true;
-                }
-            }
-            if blink.blink(time.delta()) {
-                *image = asset_server.load("buddy/face/blink.png");
+    for (side, face, emotion, mut blink, parts, side_tracker, face_tracker, emotion_tracker) in
+        buddies.iter_mut()
+    {
+        let was_blinking = blink.is_blinking;
+        let is_blinking = blink.blink(time.delta());
+        if !side_tracker.is_changed()
+            && !face_tracker.is_changed()
+            && !emotion_tracker.is_changed()
+            && was_blinking == is_blinking
+        {
+            continue;
+        }
+
+        if let Ok((mut image, mut sprite)) = faces.get_mut(parts.face) {
+            sprite.flip_x = side.faces_left();
+            *image = if is_blinking && emotion.can_blink() {
+                assets.face_blink.clone()
             } else {
-                *image = asset_server.load(face.get_path());
-            }
+                emotion
+                    .get_image(&assets)
+                    .unwrap_or_else(|| face.get_image(&assets))
+            };
         }
     }
 }
@@ -571,27 +818,45 @@ fn wobble_buddy(time: Res<Time>, mut buddies: Query<(&mut Transform, &mut BuddyW
 }
 
 fn set_health_counter(
-    parents: Query<&Parent>,
-    mut counters: Query<(&mut Text, &Parent), With<HealthCounter>>,
-    buddies: Query<&Health>,
+    buddies: Query<(&Health, &BuddyParts), Changed<Health>>,
+    mut counters: Query<&mut Text, With<HealthCounter>>,
 ) {
-    for (mut text, parent) in counters.iter_mut() {
-        let buddy_entity = parents.get(parent.0).unwrap().0;
-        if let Ok(health) = buddies.get(buddy_entity) {
+    for (health, parts) in buddies.iter() {
+        if let Ok(mut text) = counters.get_mut(parts.health_counter) {
             text.sections[0].value = health.0.value().to_string();
         }
     }
 }
 
 fn set_strength_counter(
-    parents: Query<&Parent>,
-    mut counters: Query<(&mut Text, &Parent), With<StrengthCounter>>,
-    buddies: Query<&Strength>,
+    buddies: Query<(&Strength, &BuddyParts), Changed<Strength>>,
+    mut counters: Query<&mut Text, With<StrengthCounter>>,
 ) {
-    for (mut text, parent) in counters.iter_mut() {
-        let buddy_entity = parents.get(parent.0).unwrap().0;
-        if let Ok(strength) = buddies.get(buddy_entity) {
+    for (strength, parts) in buddies.iter() {
+        if let Ok(mut text) = counters.get_mut(parts.strength_counter) {
             text.sections[0].value = strength.0.value().to_string();
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_color_beats_exactly_one_other() {
+        for color in BuddyColor::COLORS {
+            assert!(!color.beats(color));
+            let beaten = BuddyColor::COLORS
+                .iter()
+                .filter(|other| color.beats(other))
+                .collect::<Vec<_>>();
+            assert_eq!(beaten.len(), 1, "{:?} beats {:?}", color, beaten);
+            // advantage only ever goes one way
+            assert!(!beaten[0].beats(color));
+        }
+        assert!(BuddyColor::Red.beats(&BuddyColor::Green));
+        assert!(BuddyColor::Green.beats(&BuddyColor::Blue));
+        assert!(BuddyColor::Blue.beats(&BuddyColor::Red));
+    }
+}
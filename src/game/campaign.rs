@@ -0,0 +1,402 @@
+use crate::{
+    game::{
+        boss::BossAbility,
+        buddy::{BuddyColor, BuddyFace, BuddyTemplate},
+        counters::{Coins, Trophies},
+        events::{BattleEnded, BattleResult},
+        return_to_menu,
+        shop::STARTING_COINS,
+        BattleMessages, GameEntities,
+    },
+    loading::GameAssets,
+    menu::{HOVERED_BUTTON, NORMAL_BUTTON},
+    AppState,
+};
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::TypeUuid,
+    ui::FocusPolicy,
+    utils::BoxedFuture,
+};
+use rand::{rngs::StdRng, SeedableRng};
+use serde::Deserialize;
+
+/// A run against a fixed line of handmade opponents instead of endless random ones. A map
+/// between battles shows how far along the campaign is. Beating an opponent moves on to the
+/// next one, and losing means a rematch with the same team.
+pub struct CampaignPlugin;
+
+impl Plugin for CampaignPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_update(AppState::Battle).with_system(advance_campaign))
+            .add_system_set(SystemSet::on_enter(AppState::Map).with_system(spawn_map))
+            .add_system_set(SystemSet::on_update(AppState::Map).with_system(map_buttons))
+            .add_system_set(SystemSet::on_exit(AppState::Map).with_system(despawn_map))
+            .add_system_set(SystemSet::on_enter(AppState::Menu).with_system(leave_campaign));
+    }
+}
+
+/// Every opponent, in the order they're fought
+pub const CAMPAIGN: &[&str] = &[
+    "campaign/01_pip.opponent",
+    "campaign/02_marigold.opponent",
+    "campaign/03_captain_cobalt.opponent",
+    "campaign/04_scarlet.opponent",
+    "campaign/05_old_bertram.opponent",
+];
+
+/// An opponent file, written in RON
+#[derive(Clone, Deserialize, TypeUuid)]
+#[uuid = "3d9a7c41-b5e2-4f08-8c6d-2a1f94e7b053"]
+pub struct Opponent {
+    pub name: String,
+    /// said on the map before the first battle against them
+    pub intro: String,
+    /// said on the map after beating the player
+    pub taunt: String,
+    /// said on the map after losing to the player
+    pub defeat: String,
+    /// front to back
+    pub team: Vec<OpponentBuddy>,
+}
+
+#[derive(Clone, Deserialize)]
+pub struct OpponentBuddy {
+    pub color: BuddyColor,
+    #[serde(default)]
+    pub face: BuddyFace,
+    pub health: usize,
+    pub strength: usize,
+    #[serde(default)]
+    pub ability: Option<BossAbility>,
+}
+
+impl OpponentBuddy {
+    fn template(&self, rng: &mut StdRng) -> BuddyTemplate {
+        BuddyTemplate::random_for_round(rng, 1)
+            .with_stats(self.health, self.strength)
+            .with_color(self.color)
+            .with_face(self.face)
+    }
+}
+
+#[derive(Default)]
+pub struct OpponentLoader;
+
+impl AssetLoader for OpponentLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let opponent = ron::de::from_bytes::<Opponent>(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(opponent));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["opponent"]
+    }
+}
+
+/// Inserted by the menu when starting the campaign. Like a daily challenge it's played in one
+/// go, so none of it is saved.
+pub struct Campaign {
+    opponents: Vec<Opponent>,
+    /// the opponent up next, which is past the end once every one is beaten
+    stage: usize,
+    /// how the last battle went, None before the first one
+    last: Option<BattleResult>,
+}
+
+impl Campaign {
+    pub fn new(opponents: Vec<Opponent>) -> Self {
+        Self {
+            opponents,
+            stage: 0,
+            last: None,
+        }
+    }
+
+    pub fn opponent(&self) -> Option<&Opponent> {
+        self.opponents.get(self.stage)
+    }
+
+    pub fn finished(&self) -> bool {
+        self.stage >= self.opponents.len()
+    }
+
+    /// The team of the opponent up next. Names and looks are rolled from their place in the
+    /// campaign, so a rematch is against the very same buddies.
+    pub fn team(&self) -> Vec<(BuddyTemplate, Option<BossAbility>)> {
+        let mut rng = StdRng::seed_from_u64(self.stage as u64);
+        self.opponent()
+            .map(|opponent| {
+                opponent
+                    .team
+                    .iter()
+                    .map(|buddy| (buddy.template(&mut rng), buddy.ability))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// what the opponents have to say about the last battle
+    fn dialogue(&self) -> Vec<String> {
+        let line = |opponent: &Opponent, said: &str| format!("{}: \"{}\"", opponent.name, said);
+        let mut lines = Vec::new();
+        match self.last {
+            Some(BattleResult::Win) => {
+                let beaten = &self.opponents[self.stage - 1];
+                lines.push(line(beaten, &beaten.defeat));
+                match self.opponent() {
+                    Some(next) => lines.push(line(next, &next.intro)),
+                    None => lines.push("Every opponent is beaten!".to_string()),
+                }
+            }
+            Some(BattleResult::Lose) => {
+                if let Some(opponent) = self.opponent() {
+                    lines.push(line(opponent, &opponent.taunt));
+                }
+                lines.push("Your team is ready for a rematch".to_string());
+            }
+            Some(BattleResult::Tie) => {
+                lines.push("Nobody won that one, so it's a rematch".to_string());
+            }
+            None => {
+                if let Some(opponent) = self.opponent() {
+                    lines.push(line(opponent, &opponent.intro));
+                }
+            }
+        }
+        lines
+    }
+}
+
+fn advance_campaign(
+    campaign: Option<ResMut<Campaign>>,
+    mut battle_ended_events: EventReader<BattleEnded>,
+) {
+    let mut campaign = match campaign {
+        Some(campaign) => campaign,
+        None => return,
+    };
+    if let Some(ended) = battle_ended_events.iter().last() {
+        campaign.last = Some(ended.result);
+        if ended.result == BattleResult::Win {
+            campaign.stage += 1;
+        }
+    }
+}
+
+struct CampaignMap(Entity);
+
+#[derive(Component, Copy, Clone)]
+enum MapButton {
+    Fight,
+    Leave,
+}
+
+const BEATEN_STOP: Color = Color::rgb(0.55, 0.75, 0.55);
+const NEXT_STOP: Color = Color::rgb(0.9, 0.75, 0.4);
+const LATER_STOP: Color = Color::rgb(0.65, 0.65, 0.65);
+const TEXT_COLOR: Color = Color::rgb(0.2, 0.2, 0.2);
+
+/// The line of opponents, with the ones beaten so far filled in, and what they have to say
+fn spawn_map(mut commands: Commands, assets: Res<GameAssets>, campaign: Res<Campaign>) {
+    let font = assets.title_font.clone();
+    let text = |value: &str, font_size: f32| TextBundle {
+        style: Style {
+            margin: Rect::all(Val::Px(4.0)),
+            ..Default::default()
+        },
+        text: Text::with_section(
+            value,
+            TextStyle {
+                font: font.clone(),
+                font_size,
+                color: TEXT_COLOR,
+            },
+            Default::default(),
+        ),
+        ..Default::default()
+    };
+    let map = commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                position_type: PositionType::Absolute,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            color: Color::NONE.into(),
+            ..Default::default()
+        })
+        .insert(FocusPolicy::Pass)
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(NodeBundle {
+                    style: Style {
+                        padding: Rect::all(Val::Px(20.0)),
+                        // ui space is y-up, so reverse the column to lay things out top to bottom
+                        flex_direction: FlexDirection::ColumnReverse,
+                        align_items: AlignItems::Center,
+                        ..Default::default()
+                    },
+                    color: Color::rgba(0.95, 0.95, 1.0, 0.95).into(),
+                    ..Default::default()
+                })
+                .with_children(|parent| {
+                    let title = if campaign.finished() {
+                        "Campaign complete!"
+                    } else {
+                        "Campaign"
+                    };
+                    parent.spawn_bundle(text(title, 70.0));
+                    parent
+                        .spawn_bundle(NodeBundle {
+                            style: Style {
+                                flex_direction: FlexDirection::Row,
+                                margin: Rect::all(Val::Px(10.0)),
+                                ..Default::default()
+                            },
+                            color: Color::NONE.into(),
+                            ..Default::default()
+                        })
+                        .with_children(|parent| {
+                            for (stage, opponent) in campaign.opponents.iter().enumerate() {
+                                // opponents further along stay a mystery until they're up next
+                                let (color, label) = if stage < campaign.stage {
+                                    (BEATEN_STOP, opponent.name.as_str())
+                                } else if stage == campaign.stage {
+                                    (NEXT_STOP, opponent.name.as_str())
+                                } else {
+                                    (LATER_STOP, "???")
+                                };
+                                parent
+                                    .spawn_bundle(NodeBundle {
+                                        style: Style {
+                                            size: Size::new(Val::Px(150.0), Val::Px(60.0)),
+                                            margin: Rect::all(Val::Px(5.0)),
+                                            justify_content: JustifyContent::Center,
+                                            align_items: AlignItems::Center,
+                                            ..Default::default()
+                                        },
+                                        color: color.into(),
+                                        ..Default::default()
+                                    })
+                                    .with_children(|parent| {
+                                        parent.spawn_bundle(text(label, 30.0));
+                                    });
+                            }
+                        });
+                    for line in campaign.dialogue() {
+                        parent.spawn_bundle(text(&line, 34.0));
+                    }
+                    parent
+                        .spawn_bundle(NodeBundle {
+                            style: Style {
+                                flex_direction: FlexDirection::Row,
+                                margin: Rect::all(Val::Px(10.0)),
+                                ..Default::default()
+                            },
+                            color: Color::NONE.into(),
+                            ..Default::default()
+                        })
+                        .with_children(|parent| {
+                            if !campaign.finished() {
+                                let label = match campaign.last {
+                                    Some(BattleResult::Lose) | Some(BattleResult::Tie) => "Rematch",
+                                    _ => "Fight",
+                                };
+                                spawn_map_button(parent, &font, label, MapButton::Fight);
+                            }
+                            spawn_map_button(parent, &font, "Menu", MapButton::Leave);
+                        });
+                });
+        })
+        .id();
+    commands.insert_resource(CampaignMap(map));
+}
+
+fn spawn_map_button(
+    parent: &mut ChildBuilder,
+    font: &Handle<Font>,
+    label: &str,
+    button: MapButton,
+) {
+    parent
+        .spawn_bundle(ButtonBundle {
+            style: Style {
+                size: Size::new(Val::Px(180.0), Val::Px(55.0)),
+                margin: Rect::all(Val::Px(5.0)),
+                // horizontally center child text
+                justify_content: JustifyContent::Center,
+                // vertically center child text
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            color: NORMAL_BUTTON.into(),
+            ..Default::default()
+        })
+        .insert(button)
+        .with_children(|parent| {
+            parent.spawn_bundle(TextBundle {
+                text: Text::with_section(
+                    label,
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: 40.0,
+                        color: Color::rgb(0.9, 0.9, 0.9),
+                    },
+                    Default::default(),
+                ),
+                ..Default::default()
+            });
+        });
+}
+
+fn map_buttons(
+    mut state: ResMut<State<AppState>>,
+    mut interactions: Query<(&Interaction, &mut UiColor, &MapButton), Changed<Interaction>>,
+) {
+    for (interaction, mut color, button) in interactions.iter_mut() {
+        match *interaction {
+            Interaction::Clicked => match button {
+                MapButton::Fight => state.set(AppState::Shop).unwrap(),
+                MapButton::Leave => state.set(AppState::Menu).unwrap(),
+            },
+            Interaction::Hovered => *color = HOVERED_BUTTON.into(),
+            Interaction::None => *color = NORMAL_BUTTON.into(),
+        }
+    }
+}
+
+fn despawn_map(mut commands: Commands, map: Option<Res<CampaignMap>>) {
+    if let Some(map) = map {
+        commands.entity(map.0).despawn_recursive();
+        commands.remove_resource::<CampaignMap>();
+    }
+}
+
+/// The coins and trophies start over for whatever the menu starts next
+fn leave_campaign(
+    mut commands: Commands,
+    campaign: Option<Res<Campaign>>,
+    battle_messages: Option<Res<BattleMessages>>,
+    mut coins: ResMut<Coins>,
+    mut trophies: ResMut<Trophies>,
+    entities: GameEntities,
+) {
+    if campaign.is_none() {
+        return;
+    }
+    commands.remove_resource::<Campaign>();
+    *coins = Coins::new(STARTING_COINS);
+    *trophies = Trophies::default();
+    return_to_menu(&mut commands, &entities, battle_messages);
+}
@@ -1,18 +1,49 @@
+use crate::{
+    game::hot_seat::{PerPlayer, Player, Players},
+    loading::GameAssets,
+};
 use bevy::prelude::*;
+use std::ops::{Index, IndexMut};
 
+/// Every player's coins
 #[derive(Default)]
-pub struct Coins(pub usize);
+pub struct Coins(PerPlayer<usize>);
 
-pub fn set_coin_text(coins: Res<Coins>, mut coin_texts: Query<&mut Text, With<CoinText>>) {
+impl Coins {
+    pub fn new(coins: usize) -> Self {
+        Self(PerPlayer::splat(coins))
+    }
+}
+
+impl Index<Player> for Coins {
+    type Output = usize;
+
+    fn index(&self, player: Player) -> &usize {
+        &self.0[player]
+    }
+}
+
+impl IndexMut<Player> for Coins {
+    fn index_mut(&mut self, player: Player) -> &mut usize {
+        &mut self.0[player]
+    }
+}
+
+/// shows the coins of the player whose turn it is
+pub fn set_coin_text(
+    coins: Res<Coins>,
+    players: Res<Players>,
+    mut coin_texts: Query<&mut Text, With<CoinText>>,
+) {
     for mut text in coin_texts.iter_mut() {
-        text.sections[0].value = format!("{}", coins.0);
+        text.sections[0].value = format!("{}", coins[players.turn]);
     }
 }
 
 #[derive(Component)]
 pub struct CoinText;
 
-pub fn spawn_coins_element(commands: &mut ChildBuilder, asset_server: &AssetServer) {
+pub fn spawn_coins_element(commands: &mut ChildBuilder, assets: &GameAssets) {
     commands
         .spawn_bundle(NodeBundle {
             style: Style {
@@ -29,7 +60,7 @@ pub fn spawn_coins_element(commands: &mut ChildBuilder, asset_server: &AssetServ
         })
         .with_children(|parent| {
             parent.spawn_bundle(ImageBundle {
-                image: UiImage(asset_server.load("money.png")),
+                image: UiImage(assets.money.clone()),
                 ..Default::default()
             });
             parent
@@ -46,7 +77,7 @@ pub fn spawn_coins_element(commands: &mut ChildBuilder, asset_server: &AssetServ
                     text: Text::with_section(
                         "0",
                         TextStyle {
-                            font: asset_server.load("font/CaveatBrush-Regular.ttf"),
+                            font: assets.number_font.clone(),
                             font_size: 100.0,
                             color: Color::hex("323232").unwrap(),
                         },
@@ -63,20 +94,26 @@ pub fn spawn_coins_element(commands: &mut ChildBuilder, asset_server: &AssetServ
 
 #[derive(Default)]
 pub struct Trophies {
-    pub won: usize,
+    /// battles each player has won
+    pub won: PerPlayer<usize>,
+    /// battles fought, which every player fights together
     pub rounds: usize,
 }
 
-pub fn set_trophies_text(trophies: Res<Trophies>, mut texts: Query<&mut Text, With<TrophyText>>) {
+pub fn set_trophies_text(
+    trophies: Res<Trophies>,
+    players: Res<Players>,
+    mut texts: Query<&mut Text, With<TrophyText>>,
+) {
     for mut text in texts.iter_mut() {
-        text.sections[0].value = format!("{}/{}", trophies.won, trophies.rounds);
+        text.sections[0].value = format!("{}/{}", trophies.won[players.turn], trophies.rounds);
     }
 }
 
 #[derive(Component)]
 pub struct TrophyText;
 
-pub fn spawn_trophies_element(commands: &mut ChildBuilder, asset_server: &AssetServer) {
+pub fn spawn_trophies_element(commands: &mut ChildBuilder, assets: &GameAssets) {
     commands
         .spawn_bundle(NodeBundle {
             style: Style {
@@ -93,7 +130,7 @@ pub fn spawn_trophies_element(commands: &mut ChildBuilder, asset_server: &AssetS
         })
         .with_children(|parent| {
             parent.spawn_bundle(ImageBundle {
-                image: UiImage(asset_server.load("trophy.png")),
+                image: UiImage(assets.trophy.clone()),
                 ..Default::default()
             });
             parent
@@ -110,7 +147,7 @@ pub fn spawn_trophies_element(commands: &mut ChildBuilder, asset_server: &AssetS
                     text: Text::with_section(
                         "0",
                         TextStyle {
-                            font: asset_server.load("font/CaveatBrush-Regular.ttf"),
+                            font: assets.number_font.clone(),
                             font_size: 100.0,
                             color: Color::hex("323232").unwrap(),
                         },
@@ -0,0 +1,270 @@
+use crate::game::{buddy::BuddyColor, counters::Trophies, hot_seat::Player, persist, rules::Rules};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::{SystemTime, UNIX_EPOCH};
+use std::{cmp::Reverse, fmt};
+
+/// A run that's the same for everyone on the same day: the shops and opponents are rolled from a
+/// seed taken from the date, under fixed rules with one modifier
+pub struct DailyPlugin;
+
+impl Plugin for DailyPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(persist::load::<DailyScores>(DailyScores::SAVE_NAME))
+            .add_system(record_daily_score);
+    }
+}
+
+/// A day in UTC, so the challenge changes at the same moment for everyone
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Date {
+    pub year: i64,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl Date {
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn today() -> Date {
+        let seconds = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since| since.as_secs());
+        Date::from_days(seconds as i64 / 86_400)
+    }
+
+    /// `SystemTime::now` panics in browsers, so the clock is read from javascript instead
+    #[cfg(target_arch = "wasm32")]
+    pub fn today() -> Date {
+        let milliseconds = js_sys::Date::now();
+        Date::from_days((milliseconds / 86_400_000.0).floor() as i64)
+    }
+
+    /// The date this many days after 1970-01-01. This is Howard Hinnant's `civil_from_days`,
+    /// which counts in 400 year eras that start on the 1st of March.
+    fn from_days(days: i64) -> Date {
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let day_of_era = z.rem_euclid(146_097);
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let month_from_march = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * month_from_march + 2) / 5 + 1;
+        let month = if month_from_march < 10 {
+            month_from_march + 3
+        } else {
+            month_from_march - 9
+        };
+        Date {
+            year: year_of_era + era * 400 + i64::from(month <= 2),
+            month: month as u32,
+            day: day as u32,
+        }
+    }
+
+    /// Spreads the date out over all 64 bits (a splitmix64 finalizer), so days next to each
+    /// other get unrelated runs
+    fn seed(&self) -> u64 {
+        let mut x = (self.year as u64 * 10_000 + self.month as u64 * 100 + self.day as u64)
+            .wrapping_add(0x9e37_79b9_7f4a_7c15);
+        x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        x ^ (x >> 31)
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+/// The twist on the usual rules for one day's challenge
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Modifier {
+    OnlyColor(BuddyColor),
+    BigShop,
+    DoubleRewards,
+}
+
+impl Modifier {
+    const ALL: &'static [Modifier] = &[
+        Modifier::OnlyColor(BuddyColor::Red),
+        Modifier::OnlyColor(BuddyColor::Green),
+        Modifier::OnlyColor(BuddyColor::Blue),
+        Modifier::BigShop,
+        Modifier::DoubleRewards,
+    ];
+    const BIG_SHOP_SIZE: usize = 5;
+
+    pub fn description(&self) -> String {
+        match self {
+            Modifier::OnlyColor(color) => {
+                let color = match color {
+                    BuddyColor::Red => "red",
+                    BuddyColor::Green => "green",
+                    BuddyColor::Blue => "blue",
+                };
+                format!("Only {} buddies", color)
+            }
+            Modifier::BigShop => format!("{} buddies in the shop", Self::BIG_SHOP_SIZE),
+            Modifier::DoubleRewards => "Double rewards".to_string(),
+        }
+    }
+
+    fn apply(&self, rules: &mut Rules) {
+        match self {
+            Modifier::OnlyColor(color) => rules.only_color = Some(*color),
+            Modifier::BigShop => rules.shop_size = Some(Self::BIG_SHOP_SIZE),
+            Modifier::DoubleRewards => rules.double_rewards = true,
+        }
+    }
+}
+
+/// Inserted by the menu when starting the day's challenge
+pub struct DailyChallenge {
+    pub date: Date,
+    pub seed: u64,
+    pub modifier: Modifier,
+}
+
+impl DailyChallenge {
+    /// the challenge is over after this many battles, so everyone's score is out of the same
+    /// number of rounds
+    pub const ROUNDS: usize = 10;
+
+    pub fn today() -> Self {
+        let date = Date::today();
+        let seed = date.seed();
+        Self {
+            date,
+            seed,
+            modifier: Modifier::ALL[(seed % Modifier::ALL.len() as u64) as usize],
+        }
+    }
+
+    /// The default rules with the day's modifier, rather than whatever the player has turned on,
+    /// so everyone's runs can be compared
+    pub fn rules(&self) -> Rules {
+        let mut rules = Rules::default();
+        self.modifier.apply(&mut rules);
+        rules
+    }
+}
+
+/// The best run on every day a challenge was played, newest first
+#[derive(Default, Serialize, Deserialize)]
+pub struct DailyScores {
+    pub days: Vec<DailyScore>,
+}
+
+#[derive(Copy, Clone, Serialize, Deserialize)]
+pub struct DailyScore {
+    pub date: Date,
+    pub trophies: usize,
+    /// the round the trophies were reached in
+    pub rounds: usize,
+}
+
+impl DailyScores {
+    const SAVE_NAME: &'static str = "daily_scores";
+
+    pub fn best(&self, date: Date) -> Option<&DailyScore> {
+        self.days.iter().find(|score| score.date == date)
+    }
+
+    fn record(&mut self, score: DailyScore) {
+        self.days.retain(|other| other.date != score.date);
+        self.days.push(score);
+        self.days.sort_by_key(|score| Reverse(score.date));
+    }
+}
+
+fn record_daily_score(
+    daily: Option<Res<DailyChallenge>>,
+    trophies: Res<Trophies>,
+    mut scores: ResMut<DailyScores>,
+) {
+    let daily = match daily {
+        Some(daily) => daily,
+        None => return,
+    };
+    if !trophies.is_changed() || trophies.rounds == 0 {
+        return;
+    }
+    let won = trophies.won[Player::One];
+    if scores
+        .best(daily.date)
+        .is_some_and(|best| best.trophies >= won)
+    {
+        return;
+    }
+    scores.record(DailyScore {
+        date: daily.date,
+        trophies: won,
+        rounds: trophies.rounds,
+    });
+    persist::save(DailyScores::SAVE_NAME, &*scores);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i64, month: u32, day: u32) -> Date {
+        Date { year, month, day }
+    }
+
+    #[test]
+    fn days_count_from_the_unix_epoch() {
+        assert_eq!(Date::from_days(0), date(1970, 1, 1));
+        assert_eq!(Date::from_days(-1), date(1969, 12, 31));
+        assert_eq!(Date::from_days(31), date(1970, 2, 1));
+        assert_eq!(Date::from_days(19_000), date(2022, 1, 8));
+    }
+
+    #[test]
+    fn leap_days_only_come_in_leap_years() {
+        // 2020 is divisible by 4, 1900 by 100 but not 400, and 2000 by 400
+        assert_eq!(Date::from_days(18_321), date(2020, 2, 29));
+        assert_eq!(Date::from_days(18_322), date(2020, 3, 1));
+        assert_eq!(Date::from_days(-25_509), date(1900, 2, 28));
+        assert_eq!(Date::from_days(-25_508), date(1900, 3, 1));
+        assert_eq!(Date::from_days(11_016), date(2000, 2, 29));
+        assert_eq!(Date::from_days(11_017), date(2000, 3, 1));
+    }
+
+    #[test]
+    fn eras_start_on_the_first_of_march() {
+        // an era is 146,097 days, and 2000-03-01 starts the one the epoch is in
+        assert_eq!(Date::from_days(11_017), date(2000, 3, 1));
+        assert_eq!(Date::from_days(11_017 - 146_097), date(1600, 3, 1));
+        assert_eq!(Date::from_days(11_016 - 146_097), date(1600, 2, 29));
+        assert_eq!(Date::from_days(11_017 + 146_097), date(2400, 3, 1));
+        assert_eq!(Date::from_days(11_016 + 146_097), date(2400, 2, 29));
+    }
+
+    #[test]
+    fn consecutive_days_follow_each_other() {
+        let mut last = Date::from_days(-800_000);
+        for days in -799_999..800_000 {
+            let next = Date::from_days(days);
+            assert!(next > last, "{} comes after {}", next, last);
+            if next.year == last.year && next.month == last.month {
+                assert_eq!(next.day, last.day + 1);
+            } else {
+                assert_eq!(next.day, 1);
+            }
+            last = next;
+        }
+    }
+
+    #[test]
+    fn nearby_days_get_different_challenges() {
+        let seeds = (0..30)
+            .map(|days| Date::from_days(19_000 + days).seed())
+            .collect::<std::collections::HashSet<_>>();
+        assert_eq!(seeds.len(), 30);
+    }
+}
@@ -0,0 +1,75 @@
+use crate::{
+    game::{events::AttackResolved, Z_MESSAGE},
+    loading::GameAssets,
+};
+use bevy::prelude::*;
+
+/// Numbers that float up out of a buddy when it gets hit
+pub struct DamageNumbersPlugin;
+
+impl Plugin for DamageNumbersPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(spawn_damage_numbers)
+            .add_system(float_damage_numbers);
+    }
+}
+
+#[derive(Component)]
+struct DamageNumber(Timer);
+
+const RISE_SPEED: f32 = 120.0;
+const NORMAL_COLOR: Color = Color::rgb(0.2, 0.2, 0.2);
+const EFFECTIVE_COLOR: Color = Color::rgb(0.9, 0.4, 0.1);
+
+fn spawn_damage_numbers(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    mut attack_events: EventReader<AttackResolved>,
+    buddies: Query<&GlobalTransform>,
+) {
+    for attack in attack_events.iter() {
+        let position = match buddies.get(attack.target) {
+            Ok(transform) => transform.translation,
+            Err(_) => continue,
+        };
+        // effective hits are bigger and brighter, so a matchup advantage is easy to spot
+        let (value, color, font_size) = if attack.effective {
+            (format!("-{}!", attack.damage), EFFECTIVE_COLOR, 110.0)
+        } else {
+            (format!("-{}", attack.damage), NORMAL_COLOR, 80.0)
+        };
+        commands
+            .spawn_bundle(Text2dBundle {
+                text: Text::with_section(
+                    value,
+                    TextStyle {
+                        font: assets.number_font.clone(),
+                        font_size,
+                        color,
+                    },
+                    TextAlignment {
+                        vertical: VerticalAlign::Center,
+                        horizontal: HorizontalAlign::Center,
+                    },
+                ),
+                transform: Transform::from_xyz(position.x, position.y + 100.0, Z_MESSAGE - 1.0),
+                ..Default::default()
+            })
+            .insert(DamageNumber(Timer::from_seconds(0.8, false)));
+    }
+}
+
+fn float_damage_numbers(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut numbers: Query<(Entity, &mut DamageNumber, &mut Transform, &mut Text)>,
+) {
+    for (entity, mut number, mut transform, mut text) in numbers.iter_mut() {
+        if number.0.tick(time.delta()).just_finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        transform.translation.y += RISE_SPEED * time.delta_seconds();
+        text.sections[0].style.color.set_a(1.0 - number.0.percent());
+    }
+}
@@ -0,0 +1,134 @@
+use crate::{
+    game::{
+        battle::Battle,
+        buddy::{Buddy, Health, Side},
+        events::{AttackResolved, BattleEnded, BattleResult},
+    },
+    loading::GameAssets,
+    AppState,
+};
+use bevy::prelude::*;
+
+pub struct EmotionPlugin;
+
+impl Plugin for EmotionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(update_emotions)
+            .add_system_set(SystemSet::on_exit(AppState::Battle).with_system(calm_buddies));
+    }
+}
+
+/// What a buddy is currently feeling. This decides which face is displayed, falling back to the
+/// buddy's own [`BuddyFace`](crate::game::buddy::BuddyFace) when it is [`Emotion::Calm`].
+/// Blinking sits on top of this.
+#[derive(Component, Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum Emotion {
+    #[default]
+    Calm,
+    Nervous,
+    Angry,
+    Hurt,
+    KnockedOut,
+    Celebrating,
+}
+
+impl Emotion {
+    /// `None` means "show the buddy's base face"
+    pub fn get_image(&self, assets: &GameAssets) -> Option<Handle<Image>> {
+        match self {
+            Emotion::Calm => None,
+            Emotion::Nervous => Some(assets.face_nervous.clone()),
+            Emotion::Angry => Some(assets.face_angry.clone()),
+            Emotion::Hurt => Some(assets.face_hurt.clone()),
+            Emotion::KnockedOut => Some(assets.face_knocked_out.clone()),
+            Emotion::Celebrating => Some(assets.face_celebrating.clone()),
+        }
+    }
+
+    /// knocked out buddies already have their eyes shut
+    pub fn can_blink(&self) -> bool {
+        *self != Emotion::KnockedOut
+    }
+}
+
+/// A short-lived emotion caused by something that just happened, which wins over the buddy's
+/// resting emotion until it times out
+#[derive(Component, Default)]
+pub struct Reaction(Option<(Emotion, Timer)>);
+
+impl Reaction {
+    fn set(&mut self, emotion: Emotion, seconds: f32) {
+        self.0 = Some((emotion, Timer::from_seconds(seconds, false)));
+    }
+}
+
+const HURT_SECONDS: f32 = 0.6;
+const CELEBRATE_SECONDS: f32 = 3.0;
+
+fn is_low_health(health: &Health) -> bool {
+    let value = health.0.value();
+    let base = health.0.base();
+    value < base && value <= (base / 3).max(1)
+}
+
+fn update_emotions(
+    time: Res<Time>,
+    battle: Res<Battle>,
+    mut attack_events: EventReader<AttackResolved>,
+    mut battle_ended_events: EventReader<BattleEnded>,
+    mut buddies: Query<(Entity, &Buddy, &Health, &Side, &mut Reaction, &mut Emotion)>,
+) {
+    let targets = attack_events
+        .iter()
+        .map(|attack| attack.target)
+        .collect::<Vec<_>>();
+    let winner = battle_ended_events
+        .iter()
+        .last()
+        .and_then(|ended| match ended.result {
+            BattleResult::Win => Some(Side::Left),
+            BattleResult::Lose => Some(Side::Right),
+            BattleResult::Tie => None,
+        });
+    let lunging = battle.lunging();
+
+    for (entity, buddy, health, side, mut reaction, mut emotion) in buddies.iter_mut() {
+        if let Some((_, timer)) = &mut reaction.0 {
+            if timer.tick(time.delta()).finished() {
+                reaction.0 = None;
+            }
+        }
+        if targets.contains(&entity) {
+            reaction.set(Emotion::Hurt, HURT_SECONDS);
+        }
+        if winner == Some(*side) && buddy.alive {
+            reaction.set(Emotion::Celebrating, CELEBRATE_SECONDS);
+        }
+
+        let is_lunging =
+            matches!(lunging, Some((left, right)) if entity == left || entity == right);
+        let next = if !buddy.alive || health.0.value() == 0 {
+            Emotion::KnockedOut
+        } else if is_lunging {
+            Emotion::Angry
+        } else if let Some((felt, _)) = &reaction.0 {
+            *felt
+        } else if is_low_health(health) {
+            Emotion::Nervous
+        } else {
+            Emotion::Calm
+        };
+
+        // only deref mutably on a change, set_buddy_face relies on change detection
+        if *emotion != next {
+            *emotion = next;
+        }
+    }
+}
+
+fn calm_buddies(mut buddies: Query<(&mut Reaction, &mut Emotion)>) {
+    for (mut reaction, mut emotion) in buddies.iter_mut() {
+        reaction.0 = None;
+        *emotion = Emotion::Calm;
+    }
+}
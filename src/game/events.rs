@@ -0,0 +1,118 @@
+use crate::game::{
+    buddy::{BuddyColor, Side},
+    name::BuddyName,
+    workshop::Upgrade,
+};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Gameplay events emitted by the shop and battle systems. Anything that wants to react to what
+/// the player (or the battle) is doing should read these instead of reaching into those systems.
+pub struct EventsPlugin;
+
+impl Plugin for EventsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<BuddyBought>()
+            .add_event::<BuddySold>()
+            .add_event::<BuddyMoved>()
+            .add_event::<BuddyUpgraded>()
+            .add_event::<ShopRefreshed>()
+            .add_event::<AttackResolved>()
+            .add_event::<BuddyFainted>()
+            .add_event::<BuddyMovedUp>()
+            .add_event::<BattleEnded>();
+    }
+}
+
+/// A shop buddy was paid for and moved onto the team
+#[allow(dead_code)]
+pub struct BuddyBought {
+    pub buddy: Entity,
+    pub price: usize,
+}
+
+/// A team buddy was dragged into the trash
+#[allow(dead_code)]
+pub struct BuddySold {
+    pub buddy: Entity,
+}
+
+/// A team buddy was dragged to a different slot
+#[allow(dead_code)]
+pub struct BuddyMoved {
+    pub buddy: Entity,
+    pub from: usize,
+    pub to: usize,
+}
+
+/// A team buddy was improved at the workshop
+#[allow(dead_code)]
+pub struct BuddyUpgraded {
+    pub buddy: Entity,
+    pub upgrade: Upgrade,
+    pub price: usize,
+}
+
+/// The shop was restocked with new buddies
+pub struct ShopRefreshed;
+
+/// Who a buddy was when an event about it was sent. The buddy may be gone by the time the event
+/// is read, so readers use this instead of looking it up.
+#[derive(Clone)]
+pub struct BuddyLabel {
+    pub name: BuddyName,
+    pub color: BuddyColor,
+}
+
+/// One front buddy hit the other. Both front buddies attack at the same time, so each exchange
+/// produces two of these.
+#[allow(dead_code)]
+pub struct AttackResolved {
+    pub attacker: Entity,
+    pub attacker_label: BuddyLabel,
+    pub target: Entity,
+    pub target_label: BuddyLabel,
+    pub damage: usize,
+    /// the attacker had the color advantage
+    pub effective: bool,
+}
+
+#[allow(dead_code)]
+pub struct BuddyFainted {
+    pub buddy: Entity,
+    pub side: Side,
+    pub label: BuddyLabel,
+}
+
+/// A buddy moved up a slot after the buddy in front of it fainted
+#[allow(dead_code)]
+pub struct BuddyMovedUp {
+    pub buddy: Entity,
+    pub side: Side,
+    pub label: BuddyLabel,
+    /// the slot it moved into, 0 is the front
+    pub slot: usize,
+}
+
+pub struct BattleEnded {
+    pub result: BattleResult,
+    pub reward: usize,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BattleResult {
+    Win,
+    Lose,
+    Tie,
+}
+
+impl BattleResult {
+    /// coins paid out at the end of a battle
+    pub fn reward(&self) -> usize {
+        match self {
+            BattleResult::Win => 5,
+            BattleResult::Lose => 2,
+            BattleResult::Tie => 3,
+        }
+    }
+}
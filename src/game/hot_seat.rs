@@ -0,0 +1,258 @@
+use crate::{
+    game::{
+        buddy::Side,
+        counters::Trophies,
+        events::{BattleEnded, BattleResult},
+    },
+    loading::GameAssets,
+    AppState,
+};
+use bevy::{prelude::*, ui::FocusPolicy};
+use serde::{Deserialize, Serialize};
+use std::ops::{Index, IndexMut};
+
+/// Local versus, where two players take turns in the shop and then fight each other's teams
+pub struct HotSeatPlugin;
+
+impl Plugin for HotSeatPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Players>()
+            .add_system_set(SystemSet::on_enter(AppState::Startup).with_system(spawn_turn_banner))
+            .add_system_set(SystemSet::on_enter(AppState::Shop).with_system(first_turn))
+            .add_system(set_turn_banner_text)
+            .add_system_set(SystemSet::on_enter(AppState::Menu).with_system(despawn_turn_banner));
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Player {
+    One,
+    Two,
+}
+
+impl Player {
+    pub const ALL: [Player; 2] = [Player::One, Player::Two];
+
+    fn index(&self) -> usize {
+        match self {
+            Player::One => 0,
+            Player::Two => 1,
+        }
+    }
+
+    /// the side this player's team stands on
+    pub fn side(&self) -> Side {
+        match self {
+            Player::One => Side::Left,
+            Player::Two => Side::Right,
+        }
+    }
+
+    pub fn other(&self) -> Player {
+        match self {
+            Player::One => Player::Two,
+            Player::Two => Player::One,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Player::One => "Player 1",
+            Player::Two => "Player 2",
+        }
+    }
+
+    /// how a battle went for this player, given how it went for the left team
+    pub fn result(&self, left_result: BattleResult) -> BattleResult {
+        match (self, left_result) {
+            (Player::Two, BattleResult::Win) => BattleResult::Lose,
+            (Player::Two, BattleResult::Lose) => BattleResult::Win,
+            _ => left_result,
+        }
+    }
+}
+
+/// A value kept separately for each player. Single player runs only ever use [`Player::One`].
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct PerPlayer<T>([T; 2]);
+
+impl<T: Copy> PerPlayer<T> {
+    pub fn splat(value: T) -> Self {
+        Self([value; 2])
+    }
+}
+
+impl<T> Index<Player> for PerPlayer<T> {
+    type Output = T;
+
+    fn index(&self, player: Player) -> &T {
+        &self.0[player.index()]
+    }
+}
+
+impl<T> IndexMut<Player> for PerPlayer<T> {
+    fn index_mut(&mut self, player: Player) -> &mut T {
+        &mut self.0[player.index()]
+    }
+}
+
+/// Who is playing and whose turn it is in the shop. The menu replaces this before starting a
+/// game.
+pub struct Players {
+    pub hot_seat: bool,
+    /// one player builds both teams, and the turn is the team being edited
+    pub sandbox: bool,
+    pub turn: Player,
+}
+
+impl Default for Players {
+    fn default() -> Self {
+        Self {
+            hot_seat: false,
+            sandbox: false,
+            turn: Player::One,
+        }
+    }
+}
+
+impl Players {
+    pub fn hot_seat() -> Self {
+        Self {
+            hot_seat: true,
+            ..Default::default()
+        }
+    }
+
+    pub fn sandbox() -> Self {
+        Self {
+            sandbox: true,
+            ..Default::default()
+        }
+    }
+
+    /// whether the right team belongs to someone at this computer, rather than being rolled (or
+    /// sent) fresh for every battle
+    pub fn owns_both_teams(&self) -> bool {
+        self.hot_seat || self.sandbox
+    }
+
+    /// whether battles count towards the single player stats, achievements and saved run
+    pub fn keeps_score(&self) -> bool {
+        !self.owns_both_teams()
+    }
+
+    /// the players taking part, in turn order
+    pub fn all(&self) -> &'static [Player] {
+        if self.hot_seat {
+            &Player::ALL
+        } else {
+            &[Player::One]
+        }
+    }
+
+    /// hands the shop to the next player, returning false if everyone has had their turn
+    pub fn next_turn(&mut self) -> bool {
+        match self.turn {
+            Player::One if self.hot_seat => {
+                self.turn = Player::Two;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+fn first_turn(mut players: ResMut<Players>) {
+    players.turn = Player::One;
+}
+
+#[derive(Component)]
+struct TurnBanner;
+
+/// The node the turn banner sits in
+struct TurnBannerRoot(Entity);
+
+fn spawn_turn_banner(mut commands: Commands, assets: Res<GameAssets>, players: Res<Players>) {
+    if !players.hot_seat {
+        return;
+    }
+    let root = commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Auto),
+                position_type: PositionType::Absolute,
+                // ui space is y-up, so this sits at the top of the screen
+                position: Rect {
+                    bottom: Val::Px(20.0),
+                    ..Default::default()
+                },
+                justify_content: JustifyContent::Center,
+                ..Default::default()
+            },
+            color: Color::NONE.into(),
+            ..Default::default()
+        })
+        .insert(FocusPolicy::Pass)
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(TextBundle {
+                    text: Text::with_section(
+                        "",
+                        TextStyle {
+                            font: assets.title_font.clone(),
+                            font_size: 44.0,
+                            color: Color::hex("323232").unwrap(),
+                        },
+                        TextAlignment {
+                            vertical: VerticalAlign::Top,
+                            horizontal: HorizontalAlign::Center,
+                        },
+                    ),
+                    ..Default::default()
+                })
+                .insert(TurnBanner)
+                .insert(FocusPolicy::Pass);
+        })
+        .id();
+    commands.insert_resource(TurnBannerRoot(root));
+}
+
+fn despawn_turn_banner(mut commands: Commands, root: Option<Res<TurnBannerRoot>>) {
+    if let Some(root) = root {
+        commands.entity(root.0).despawn_recursive();
+        commands.remove_resource::<TurnBannerRoot>();
+    }
+}
+
+/// Says whose turn it is while shopping, and who won once a battle is over
+fn set_turn_banner_text(
+    players: Res<Players>,
+    trophies: Res<Trophies>,
+    state: Res<State<AppState>>,
+    mut battle_ended_events: EventReader<BattleEnded>,
+    mut banners: Query<&mut Text, With<TurnBanner>>,
+) {
+    let value = if let Some(ended) = battle_ended_events.iter().last() {
+        match ended.result {
+            BattleResult::Win => format!("{} wins!", Player::One.name()),
+            BattleResult::Lose => format!("{} wins!", Player::Two.name()),
+            BattleResult::Tie => "It's a tie!".to_string(),
+        }
+    } else if players.is_changed() || state.is_changed() || trophies.is_changed() {
+        match state.current() {
+            AppState::Shop => format!(
+                "{}'s turn\nTrophies {} - {}",
+                players.turn.name(),
+                trophies.won[Player::One],
+                trophies.won[Player::Two]
+            ),
+            AppState::Battle => format!("{} vs {}", Player::One.name(), Player::Two.name()),
+            _ => return,
+        }
+    } else {
+        return;
+    };
+    for mut text in banners.iter_mut() {
+        text.sections[0].value = value.clone();
+    }
+}
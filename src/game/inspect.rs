@@ -0,0 +1,285 @@
+use crate::{
+    game::{
+        buddy::{Buddy, Health, Side, Strength},
+        hot_seat::Players,
+        name::BuddyName,
+        shop::{cursor_world, on_buddy},
+        ui::UiRoot,
+    },
+    loading::GameAssets,
+    AppState,
+};
+use bevy::prelude::*;
+
+/// Shows the name and stats of the buddy under the cursor while shopping, and lets the player
+/// rename their own buddies
+pub struct InspectPlugin;
+
+impl Plugin for InspectPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Inspect>()
+            .add_system_set(SystemSet::on_enter(AppState::Shop).with_system(spawn_inspect_panel))
+            .add_system_set(
+                SystemSet::on_update(AppState::Shop)
+                    .with_system(hover_buddy)
+                    .with_system(rename_buddy)
+                    .with_system(update_inspect_panel),
+            )
+            .add_system_set(SystemSet::on_exit(AppState::Shop).with_system(exit_inspect));
+    }
+}
+
+#[derive(Default)]
+pub struct Inspect {
+    hovered: Option<Entity>,
+    renaming: Option<Renaming>,
+}
+
+struct Renaming {
+    buddy: Entity,
+    original: String,
+}
+
+impl Inspect {
+    /// the buddy being renamed wins over whatever the cursor happens to be on
+    fn shown(&self) -> Option<Entity> {
+        match &self.renaming {
+            Some(renaming) => Some(renaming.buddy),
+            None => self.hovered,
+        }
+    }
+}
+
+struct InspectPanel {
+    root: Entity,
+    name: Entity,
+    stats: Entity,
+    hint: Entity,
+}
+
+const PANEL_COLOR: Color = Color::rgba(0.95, 0.95, 1.0, 0.9);
+
+fn spawn_inspect_panel(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    ui_root: Query<Entity, With<UiRoot>>,
+) {
+    let text = |value: &str, font: &Handle<Font>, font_size: f32| TextBundle {
+        text: Text::with_section(
+            value,
+            TextStyle {
+                font: font.clone(),
+                font_size,
+                color: Color::hex("323232").unwrap(),
+            },
+            Default::default(),
+        ),
+        ..Default::default()
+    };
+
+    let mut root = None;
+    let mut name = None;
+    let mut stats = None;
+    let mut hint = None;
+    commands.entity(ui_root.single()).with_children(|parent| {
+        root = Some(
+            parent
+                .spawn_bundle(NodeBundle {
+                    style: Style {
+                        size: Size::new(Val::Px(300.0), Val::Auto),
+                        position_type: PositionType::Absolute,
+                        // ui space is y-up, so this sits in the bottom left corner
+                        position: Rect {
+                            top: Val::Px(20.0),
+                            left: Val::Px(20.0),
+                            ..Default::default()
+                        },
+                        padding: Rect::all(Val::Px(10.0)),
+                        flex_direction: FlexDirection::ColumnReverse,
+                        align_items: AlignItems::FlexStart,
+                        ..Default::default()
+                    },
+                    color: Color::NONE.into(),
+                    ..Default::default()
+                })
+                .with_children(|parent| {
+                    name = Some(parent.spawn_bundle(text("", &assets.title_font, 48.0)).id());
+                    stats = Some(parent.spawn_bundle(text("", &assets.title_font, 32.0)).id());
+                    hint = Some(parent.spawn_bundle(text("", &assets.title_font, 24.0)).id());
+                })
+                .id(),
+        );
+    });
+
+    commands.insert_resource(InspectPanel {
+        root: root.unwrap(),
+        name: name.unwrap(),
+        stats: stats.unwrap(),
+        hint: hint.unwrap(),
+    });
+}
+
+fn hover_buddy(
+    mut inspect: ResMut<Inspect>,
+    windows: Res<Windows>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    buddies: Query<(Entity, &Transform), With<Buddy>>,
+) {
+    let hovered = cursor_world(&windows, &cameras).and_then(|cursor| {
+        buddies
+            .iter()
+            .find(|(_, transform)| on_buddy(cursor, transform))
+            .map(|(entity, _)| entity)
+    });
+    if inspect.hovered != hovered {
+        inspect.hovered = hovered;
+    }
+}
+
+fn rename_buddy(
+    mut inspect: ResMut<Inspect>,
+    players: Res<Players>,
+    mouse_button: Res<Input<MouseButton>>,
+    keys: Res<Input<KeyCode>>,
+    mut characters: EventReader<ReceivedCharacter>,
+    mut buddies: Query<(&Side, &mut BuddyName)>,
+) {
+    // always drain typed characters, so nothing typed before renaming started leaks in
+    let typed = characters
+        .iter()
+        .map(|typed| typed.char)
+        .collect::<Vec<_>>();
+
+    if mouse_button.just_pressed(MouseButton::Right) {
+        finish_renaming(&mut inspect, &mut buddies, false);
+        if let Some(buddy) = inspect.hovered {
+            if let Ok((side, name)) = buddies.get_mut(buddy) {
+                // only the team of the player whose turn it is can be renamed
+                if *side == players.turn.side() {
+                    inspect.renaming = Some(Renaming {
+                        buddy,
+                        original: name.0.clone(),
+                    });
+                }
+            }
+        }
+        return;
+    }
+
+    let buddy = match &inspect.renaming {
+        Some(renaming) => renaming.buddy,
+        None => return,
+    };
+    if keys.just_pressed(KeyCode::Return) {
+        finish_renaming(&mut inspect, &mut buddies, false);
+        return;
+    }
+    if keys.just_pressed(KeyCode::Escape) {
+        finish_renaming(&mut inspect, &mut buddies, true);
+        return;
+    }
+
+    let mut name = match buddies.get_mut(buddy) {
+        Ok((_, name)) => name,
+        // the buddy was trashed mid-rename
+        Err(_) => {
+            inspect.renaming = None;
+            return;
+        }
+    };
+    if keys.just_pressed(KeyCode::Back) {
+        name.0.pop();
+    }
+    for character in typed {
+        let allowed = character.is_alphanumeric() || matches!(character, ' ' | '-' | '\'');
+        if allowed && name.0.chars().count() < BuddyName::MAX_LEN {
+            name.0.push(character);
+        }
+    }
+}
+
+fn finish_renaming(
+    inspect: &mut Inspect,
+    buddies: &mut Query<(&Side, &mut BuddyName)>,
+    cancel: bool,
+) {
+    if let Some(renaming) = inspect.renaming.take() {
+        if let Ok((_, mut name)) = buddies.get_mut(renaming.buddy) {
+            let trimmed = name.0.trim().to_string();
+            name.0 = if cancel || trimmed.is_empty() {
+                renaming.original
+            } else {
+                trimmed
+            };
+        }
+    }
+}
+
+fn update_inspect_panel(
+    inspect: Res<Inspect>,
+    players: Res<Players>,
+    panel: Res<InspectPanel>,
+    buddies: Query<(&Side, &BuddyName, &Health, &Strength)>,
+    mut texts: Query<&mut Text>,
+    mut colors: Query<&mut UiColor>,
+) {
+    let shown = inspect.shown().and_then(|buddy| buddies.get(buddy).ok());
+    let renaming = inspect.renaming.is_some();
+    let (name, stats, hint) = match shown {
+        Some((side, name, health, strength)) => (
+            if renaming {
+                format!("{}_", name.0)
+            } else {
+                name.0.clone()
+            },
+            format!(
+                "Health {}   Strength {}",
+                health.0.value(),
+                strength.0.value()
+            ),
+            if renaming {
+                "Enter to keep, Esc to cancel"
+            } else if *side == players.turn.side() {
+                "Right click to rename"
+            } else {
+                ""
+            },
+        ),
+        None => (String::new(), String::new(), ""),
+    };
+
+    // only touch the text when it changes, so it isn't laid out again every frame
+    for (entity, value) in [
+        (panel.name, &*name),
+        (panel.stats, &*stats),
+        (panel.hint, hint),
+    ] {
+        if let Ok(mut text) = texts.get_mut(entity) {
+            if text.sections[0].value != value {
+                text.sections[0].value = value.to_string();
+            }
+        }
+    }
+    if let Ok(mut color) = colors.get_mut(panel.root) {
+        let target = if shown.is_some() {
+            PANEL_COLOR
+        } else {
+            Color::NONE
+        };
+        if color.0 != target {
+            color.0 = target;
+        }
+    }
+}
+
+fn exit_inspect(
+    mut commands: Commands,
+    mut inspect: ResMut<Inspect>,
+    panel: Res<InspectPanel>,
+    mut buddies: Query<(&Side, &mut BuddyName)>,
+) {
+    finish_renaming(&mut inspect, &mut buddies, false);
+    inspect.hovered = None;
+    commands.entity(panel.root).despawn_recursive();
+    commands.remove_resource::<InspectPanel>();
+}
@@ -0,0 +1,132 @@
+use crate::{
+    game::{
+        counters::{Coins, Trophies},
+        daily::DailyChallenge,
+        hot_seat::Players,
+        return_to_menu,
+        rules::Rules,
+        run_rng::RunRng,
+        shop::STARTING_COINS,
+        BattleMessages, GameEntities,
+    },
+    loading::GameAssets,
+    menu::{HOVERED_BUTTON, NORMAL_BUTTON},
+    AppState,
+};
+use bevy::prelude::*;
+
+/// A way back to the menu from the modes that aren't saved: local versus and the sandbox, which
+/// go on for as long as the players like, and the daily challenge, which can be left before its
+/// last round
+pub struct LeavePlugin;
+
+impl Plugin for LeavePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_enter(AppState::Startup).with_system(spawn_leave_button))
+            .add_system_set(SystemSet::on_update(AppState::Shop).with_system(leave_button))
+            .add_system_set(SystemSet::on_enter(AppState::Menu).with_system(leave_game));
+    }
+}
+
+/// whether the game being played is one of the modes this plugin looks after
+fn can_leave(players: &Players, daily: &Option<Res<DailyChallenge>>) -> bool {
+    players.owns_both_teams() || daily.is_some()
+}
+
+struct LeaveButtonRoot(Entity);
+
+#[derive(Component)]
+struct LeaveButton;
+
+fn spawn_leave_button(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    players: Res<Players>,
+    daily: Option<Res<DailyChallenge>>,
+) {
+    if !can_leave(&players, &daily) {
+        return;
+    }
+    let root = commands
+        .spawn_bundle(ButtonBundle {
+            style: Style {
+                size: Size::new(Val::Px(130.0), Val::Px(40.0)),
+                position_type: PositionType::Absolute,
+                // ui space is y-up, so this sits in the top left corner
+                position: Rect {
+                    bottom: Val::Px(20.0),
+                    left: Val::Px(20.0),
+                    ..Default::default()
+                },
+                // horizontally center child text
+                justify_content: JustifyContent::Center,
+                // vertically center child text
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            color: NORMAL_BUTTON.into(),
+            ..Default::default()
+        })
+        .insert(LeaveButton)
+        .with_children(|parent| {
+            parent.spawn_bundle(TextBundle {
+                text: Text::with_section(
+                    "Menu",
+                    TextStyle {
+                        font: assets.title_font.clone(),
+                        font_size: 30.0,
+                        color: Color::rgb(0.9, 0.9, 0.9),
+                    },
+                    Default::default(),
+                ),
+                ..Default::default()
+            });
+        })
+        .id();
+    commands.insert_resource(LeaveButtonRoot(root));
+}
+
+fn leave_button(
+    mut state: ResMut<State<AppState>>,
+    mut interactions: Query<
+        (&Interaction, &mut UiColor),
+        (Changed<Interaction>, With<LeaveButton>),
+    >,
+) {
+    for (interaction, mut color) in interactions.iter_mut() {
+        match *interaction {
+            Interaction::Clicked => state.set(AppState::Menu).unwrap(),
+            Interaction::Hovered => *color = HOVERED_BUTTON.into(),
+            Interaction::None => *color = NORMAL_BUTTON.into(),
+        }
+    }
+}
+
+/// The players, rules, seed, coins and trophies the mode brought along are swapped back for a
+/// single player run's, so whatever the menu starts next starts from scratch
+fn leave_game(
+    mut commands: Commands,
+    players: Res<Players>,
+    daily: Option<Res<DailyChallenge>>,
+    root: Option<Res<LeaveButtonRoot>>,
+    battle_messages: Option<Res<BattleMessages>>,
+    mut coins: ResMut<Coins>,
+    mut trophies: ResMut<Trophies>,
+    entities: GameEntities,
+) {
+    if !can_leave(&players, &daily) {
+        return;
+    }
+    commands.remove_resource::<DailyChallenge>();
+    commands.insert_resource(Players::default());
+    commands.insert_resource(Rules::saved());
+    commands.insert_resource(RunRng::default());
+    *coins = Coins::new(STARTING_COINS);
+    *trophies = Trophies::default();
+
+    if let Some(root) = root {
+        commands.entity(root.0).despawn_recursive();
+        commands.remove_resource::<LeaveButtonRoot>();
+    }
+    return_to_menu(&mut commands, &entities, battle_messages);
+}
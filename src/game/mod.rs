@@ -1,20 +1,74 @@
+pub mod achievements;
 pub mod animate;
+pub mod appearance;
 pub mod battle;
+pub mod battle_log;
+pub mod boss;
 pub mod buddy;
+pub mod campaign;
 pub mod counters;
+pub mod daily;
+pub mod damage_numbers;
+pub mod emotion;
+pub mod events;
+pub mod hot_seat;
+pub mod inspect;
+pub mod leave;
+pub mod name;
+pub mod net;
+pub mod online;
 pub mod pad;
+pub mod persist;
+pub mod puzzle;
+pub mod replay;
+pub mod rules;
+pub mod run_rng;
+pub mod sandbox;
+pub mod save;
+pub mod share;
 pub mod shop;
+pub mod sim;
+pub mod stats;
+pub mod synergy;
 pub mod ui;
+pub mod workshop;
 
 use crate::{
     game::{
-        animate::AnimatePlugin, battle::BattlePlugin, buddy::BuddyPlugin, counters::Coins,
-        pad::spawn_pads, shop::ShopPlugin,
+        achievements::AchievementsPlugin,
+        animate::AnimatePlugin,
+        battle::BattlePlugin,
+        battle_log::BattleLogPlugin,
+        boss::BossPlugin,
+        buddy::{Buddy, BuddyPlugin},
+        campaign::{Campaign, CampaignPlugin},
+        counters::{Coins, Trophies},
+        daily::DailyPlugin,
+        damage_numbers::DamageNumbersPlugin,
+        emotion::EmotionPlugin,
+        events::EventsPlugin,
+        hot_seat::HotSeatPlugin,
+        inspect::InspectPlugin,
+        leave::LeavePlugin,
+        online::OnlinePlugin,
+        pad::{spawn_pads, Pad},
+        puzzle::PuzzlePlugin,
+        replay::{ReplayPlayback, ReplayPlugin},
+        rules::RulesPlugin,
+        run_rng::RunRng,
+        sandbox::SandboxPlugin,
+        save::{restore_run, RunSave},
+        share::SharePlugin,
+        shop::ShopPlugin,
+        stats::StatsPlugin,
+        synergy::SynergyPlugin,
+        workshop::WorkshopPlugin,
     },
+    loading::GameAssets,
     AppState,
 };
 use bevy::prelude::*;
-use ui::spawn_ui;
+use ui::{spawn_ui, UiRoot};
 
 const Z_FOREGROUND: f32 = 10.0;
 const Z_PAD: f32 = 11.0;
@@ -25,11 +79,32 @@ pub struct GamePlugin;
 
 impl Plugin for GamePlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(Coins(20))
+        app.insert_resource(Coins::new(20))
+            .init_resource::<RunRng>()
+            .add_plugin(EventsPlugin)
+            .add_plugin(RulesPlugin)
+            .add_plugin(DailyPlugin)
+            .add_plugin(PuzzlePlugin)
+            .add_plugin(CampaignPlugin)
+            .add_plugin(HotSeatPlugin)
+            .add_plugin(OnlinePlugin)
+            .add_plugin(SandboxPlugin)
+            .add_plugin(LeavePlugin)
             .add_plugin(BuddyPlugin)
+            .add_plugin(EmotionPlugin)
             .add_plugin(AnimatePlugin)
             .add_plugin(ShopPlugin)
+            .add_plugin(InspectPlugin)
+            .add_plugin(WorkshopPlugin)
             .add_plugin(BattlePlugin)
+            .add_plugin(BossPlugin)
+            .add_plugin(ReplayPlugin)
+            .add_plugin(BattleLogPlugin)
+            .add_plugin(SharePlugin)
+            .add_plugin(SynergyPlugin)
+            .add_plugin(DamageNumbersPlugin)
+            .add_plugin(StatsPlugin)
+            .add_plugin(AchievementsPlugin)
             .add_system_set(SystemSet::on_enter(AppState::Startup).with_system(setup_game));
     }
 }
@@ -40,24 +115,69 @@ pub struct BattleMessages {
     you_tie: Entity,
 }
 
+impl BattleMessages {
+    pub fn entities(&self) -> [Entity; 3] {
+        [self.you_win, self.you_lose, self.you_tie]
+    }
+}
+
+#[derive(Component)]
+pub struct Foreground;
+
+/// Everything [`setup_game`] spawns that outlives a single state, and every buddy
+pub type GameEntities<'w, 's> =
+    Query<'w, 's, Entity, Or<(With<Buddy>, With<Pad>, With<UiRoot>, With<Foreground>)>>;
+
+/// Modes without a run to go back to (replays, puzzles, campaigns, daily challenges, versus
+/// games and the sandbox) tear the game down with this, so the next game the menu starts is set
+/// up from scratch
+pub fn return_to_menu(
+    commands: &mut Commands,
+    entities: &GameEntities,
+    battle_messages: Option<Res<BattleMessages>>,
+) {
+    for entity in entities.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    if let Some(battle_messages) = battle_messages {
+        for entity in battle_messages.entities() {
+            commands.entity(entity).despawn_recursive();
+        }
+        commands.remove_resource::<BattleMessages>();
+    }
+}
+
 pub fn setup_game(
     mut commands: Commands,
-    asset_server: Res<AssetServer>,
+    assets: Res<GameAssets>,
     mut state: ResMut<State<AppState>>,
+    mut coins: ResMut<Coins>,
+    mut trophies: ResMut<Trophies>,
+    run: Option<Res<RunSave>>,
+    playback: Option<Res<ReplayPlayback>>,
+    campaign: Option<Res<Campaign>>,
 ) {
-    spawn_ui(&mut commands, &asset_server);
+    spawn_ui(&mut commands, &assets);
 
-    commands.spawn_bundle(SpriteBundle {
-        texture: asset_server.load("foreground.png"),
-        transform: Transform::from_xyz(0.0, 100.0, Z_FOREGROUND),
-        ..Default::default()
-    });
+    // set by the menu when continuing a saved run
+    if let Some(run) = run {
+        restore_run(&mut commands, &assets, &run, &mut coins, &mut trophies);
+        commands.remove_resource::<RunSave>();
+    }
+
+    commands
+        .spawn_bundle(SpriteBundle {
+            texture: assets.foreground.clone(),
+            transform: Transform::from_xyz(0.0, 100.0, Z_FOREGROUND),
+            ..Default::default()
+        })
+        .insert(Foreground);
 
-    spawn_pads(&mut commands, &asset_server);
+    spawn_pads(&mut commands, &assets);
 
     let you_win = commands
         .spawn_bundle(SpriteBundle {
-            texture: asset_server.load("you_win.png"),
+            texture: assets.you_win.clone(),
             visibility: Visibility { is_visible: false },
             transform: Transform::from_xyz(0.0, 100.0, Z_MESSAGE),
             ..Default::default()
@@ -66,7 +186,7 @@ pub fn setup_game(
 
     let you_lose = commands
         .spawn_bundle(SpriteBundle {
-            texture: asset_server.load("you_lose.png"),
+            texture: assets.you_lose.clone(),
             visibility: Visibility { is_visible: false },
             transform: Transform::from_xyz(0.0, 100.0, Z_MESSAGE),
             ..Default::default()
@@ -75,7 +195,7 @@ pub fn setup_game(
 
     let you_tie = commands
         .spawn_bundle(SpriteBundle {
-            texture: asset_server.load("you_tie.png"),
+            texture: assets.you_tie.clone(),
             visibility: Visibility { is_visible: false },
             transform: Transform::from_xyz(0.0, 200.0, Z_MESSAGE),
             ..Default::default()
@@ -86,5 +206,13 @@ pub fn setup_game(
         you_lose,
         you_tie,
     });
-    state.set(AppState::Shop).unwrap();
+    // replays skip the shop and go straight to the recorded battle, and a campaign opens on
+    // its map
+    if playback.is_some() {
+        state.set(AppState::Battle).unwrap();
+    } else if campaign.is_some() {
+        state.set(AppState::Map).unwrap();
+    } else {
+        state.set(AppState::Shop).unwrap();
+    }
 }
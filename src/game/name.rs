@@ -0,0 +1,112 @@
+use crate::game::appearance::splitmix;
+use bevy::prelude::*;
+use rand::{seq::SliceRandom, Rng};
+use serde::{Deserialize, Serialize};
+
+#[derive(Component, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BuddyName(pub String);
+
+const FIRST_SYLLABLES: &[&str] = &[
+    "bo", "bi", "ca", "da", "fi", "flo", "gu", "ja", "ki", "lu", "ma", "mo", "nu", "pi", "po",
+    "ro", "su", "ta", "wi", "zu",
+];
+const MIDDLE_SYLLABLES: &[&str] = &[
+    "b", "bb", "d", "dd", "g", "l", "ll", "m", "n", "p", "pp", "r", "s", "t", "z",
+];
+const LAST_SYLLABLES: &[&str] = &[
+    "a", "bo", "do", "ey", "i", "ie", "kins", "les", "lo", "ly", "o", "sy", "ster", "y", "zo",
+];
+
+impl BuddyName {
+    /// players can't type names longer than this
+    pub const MAX_LEN: usize = 12;
+
+    /// A name like "Pobbles" or "Kizo", built from the syllable tables above
+    pub fn generate(rng: &mut impl Rng) -> Self {
+        let first = FIRST_SYLLABLES.choose(rng).unwrap();
+        let middle = if rng.gen_bool(0.6) {
+            MIDDLE_SYLLABLES.choose(rng).copied()
+        } else {
+            None
+        };
+        Self::from_syllables(first, middle, LAST_SYLLABLES.choose(rng).unwrap())
+    }
+
+    /// A name that only depends on the seed, and stays the same between versions of `rand`
+    pub fn from_seed(seed: u64) -> Self {
+        let pick = |salt: u64, syllables: &'static [&'static str]| {
+            syllables[(splitmix(seed, salt) % syllables.len() as u64) as usize]
+        };
+        let middle = (splitmix(seed, 2) % 10 < 6).then(|| pick(3, MIDDLE_SYLLABLES));
+        Self::from_syllables(pick(1, FIRST_SYLLABLES), middle, pick(4, LAST_SYLLABLES))
+    }
+
+    fn from_syllables(first: &str, middle: Option<&str>, last: &str) -> Self {
+        let name = [first, middle.unwrap_or_default(), last].concat();
+        let mut chars = name.chars();
+        let name = match chars.next() {
+            Some(first) => first.to_uppercase().chain(chars).collect(),
+            None => name,
+        };
+        Self(name)
+    }
+}
+
+impl Default for BuddyName {
+    fn default() -> Self {
+        Self::generate(&mut rand::thread_rng())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn names_are_capitalized_words_that_fit_the_rename_box() {
+        let mut rng = StdRng::seed_from_u64(34);
+        for _ in 0..1000 {
+            let BuddyName(name) = BuddyName::generate(&mut rng);
+            assert!(name.len() <= BuddyName::MAX_LEN, "{} is too long", name);
+            assert!(name.chars().all(|c| c.is_ascii_alphabetic()), "{}", name);
+            assert!(
+                name.starts_with(|c: char| c.is_ascii_uppercase()),
+                "{}",
+                name
+            );
+            assert!(
+                name[1..].chars().all(|c| c.is_ascii_lowercase()),
+                "{}",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn the_same_rng_gives_the_same_names() {
+        let mut a = StdRng::seed_from_u64(7);
+        let mut b = StdRng::seed_from_u64(7);
+        for _ in 0..20 {
+            assert_eq!(BuddyName::generate(&mut a), BuddyName::generate(&mut b));
+        }
+    }
+
+    #[test]
+    fn seeded_names_stay_the_same() {
+        // pinned, so share codes keep importing the names they always have
+        let names = (0..4)
+            .map(|seed| BuddyName::from_seed(seed).0)
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["Rogi", "Floie", "Makins", "Pipdo"]);
+    }
+
+    #[test]
+    fn there_is_some_variety() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let names = (0..100)
+            .map(|_| BuddyName::generate(&mut rng).0)
+            .collect::<std::collections::HashSet<_>>();
+        assert!(names.len() > 50, "only {} distinct names", names.len());
+    }
+}
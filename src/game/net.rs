@@ -0,0 +1,67 @@
+//! The messages online clients and the `bab-relay` server send each other. Every message is one
+//! line of RON, so a match can be followed with nothing more than `nc`.
+
+use crate::game::{buddy::BuddyTemplate, events::BattleResult, hot_seat::Player, rules::Rules};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::io::{self, BufRead, Write};
+
+/// The relay only ever listens on this machine
+pub const RELAY_HOST: &str = "127.0.0.1";
+pub const RELAY_PORT: u16 = 7878;
+
+/// How long both players get to shop before their teams are locked in for them
+pub const SHOP_SECONDS: f32 = 45.0;
+
+#[derive(Clone, Serialize, Deserialize)]
+pub enum ClientMessage {
+    /// sent once after connecting. Players are only matched with someone on the same rules, so
+    /// both clients fight the same battle.
+    Hello { rules_version: u32, rules: Rules },
+    /// the team to fight this round with, in slot order
+    LockIn { team: Vec<BuddyTemplate> },
+    /// how the battle went for the left team
+    Finished { result: BattleResult },
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub enum RelayMessage {
+    /// nobody else is looking for a match yet
+    Waiting,
+    /// the left team in every battle belongs to [`Player::One`]
+    Matched {
+        seat: Player,
+    },
+    ShopOpen {
+        round: usize,
+        seconds: f32,
+    },
+    /// both teams are locked in, in the order every client fights them
+    Fight {
+        left: Vec<BuddyTemplate>,
+        right: Vec<BuddyTemplate>,
+    },
+    /// both clients saw the same result
+    Verified {
+        result: BattleResult,
+    },
+    /// the clients disagree about how the battle went, so the match is over
+    Desync,
+    OpponentLeft,
+}
+
+pub fn send<T: Serialize>(writer: &mut impl Write, message: &T) -> io::Result<()> {
+    let line = ron::to_string(message).map_err(io::Error::other)?;
+    writeln!(writer, "{}", line)?;
+    writer.flush()
+}
+
+/// The next message, or `None` once the other end has hung up
+pub fn receive<T: DeserializeOwned>(reader: &mut impl BufRead) -> io::Result<Option<T>> {
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+    ron::from_str(&line)
+        .map(Some)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
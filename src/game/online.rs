@@ -0,0 +1,419 @@
+use crate::{
+    game::{
+        battle::{opponent_for_round, Battle},
+        buddy::{Buddy, BuddyTemplate, Side},
+        counters::Trophies,
+        events::{BattleEnded, BattleResult},
+        hot_seat::Player,
+        net::{self, ClientMessage, RelayMessage, RELAY_HOST, RELAY_PORT},
+        rules::Rules,
+        run_rng::RunRng,
+        save::{team_templates, TeamQuery},
+        workshop::Workshop,
+    },
+    loading::GameAssets,
+    AppState,
+};
+use bevy::{prelude::*, ui::FocusPolicy};
+use std::{
+    io::{self, BufReader},
+    net::{Shutdown, TcpStream},
+    sync::{
+        mpsc::{self, Receiver, TryRecvError},
+        Mutex,
+    },
+    thread,
+};
+
+/// Head to head games against another player, through the relay started with
+/// `cargo run --bin bab-relay`
+pub struct OnlinePlugin;
+
+impl Plugin for OnlinePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_enter(AppState::Startup).with_system(spawn_online_status))
+            .add_system_set(SystemSet::on_update(AppState::Shop).with_system(shop_timer))
+            .add_system(receive_relay_messages)
+            .add_system(report_result)
+            .add_system(set_online_status_text);
+    }
+}
+
+#[derive(Copy, Clone, PartialEq)]
+enum OnlineStatus {
+    Waiting,
+    Shopping,
+    LockedIn,
+    Fighting,
+    /// how the last battle went for us, once the relay has checked both clients agree
+    Verified(BattleResult),
+    Desync,
+    OpponentLeft,
+    Disconnected,
+}
+
+/// A connection to the relay. Inserted by the menu when starting an online game, and kept around
+/// after the match is over so the status can still be shown.
+pub struct Online {
+    stream: TcpStream,
+    inbox: Mutex<Receiver<RelayMessage>>,
+    seat: Player,
+    round: usize,
+    shop_timer: Option<Timer>,
+    status: OnlineStatus,
+    /// the teams for a fight the relay started before we were back in the shop
+    pending_fight: Option<(Vec<BuddyTemplate>, Vec<BuddyTemplate>)>,
+}
+
+impl Online {
+    pub fn connect(rules: &Rules) -> io::Result<Online> {
+        let stream = TcpStream::connect((RELAY_HOST, RELAY_PORT))?;
+        stream.set_nodelay(true)?;
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let (sender, inbox) = mpsc::channel();
+        // stops once the relay hangs up, or the connection is dropped
+        thread::spawn(move || {
+            while let Ok(Some(message)) = net::receive(&mut reader) {
+                if sender.send(message).is_err() {
+                    return;
+                }
+            }
+        });
+        let online = Online {
+            stream,
+            inbox: Mutex::new(inbox),
+            seat: Player::One,
+            round: 0,
+            shop_timer: None,
+            status: OnlineStatus::Waiting,
+            pending_fight: None,
+        };
+        online.send(&ClientMessage::Hello {
+            rules_version: Rules::VERSION,
+            rules: rules.clone(),
+        });
+        Ok(online)
+    }
+
+    fn send(&self, message: &ClientMessage) {
+        if let Err(err) = net::send(&mut &self.stream, message) {
+            warn!("couldn't reach the relay: {}", err);
+        }
+    }
+
+    /// still in a match, rather than carrying on against the computer
+    pub fn playing(&self) -> bool {
+        matches!(
+            self.status,
+            OnlineStatus::Waiting
+                | OnlineStatus::Shopping
+                | OnlineStatus::LockedIn
+                | OnlineStatus::Fighting
+                | OnlineStatus::Verified(_)
+        )
+    }
+
+    pub fn locked_in(&self) -> bool {
+        self.status == OnlineStatus::LockedIn
+    }
+
+    /// Sends the team to the relay, if the shop is open. Nothing done in the shop afterwards
+    /// counts, so the shop is closed up too.
+    pub fn lock_in(
+        &mut self,
+        commands: &mut Commands,
+        workshop: &mut Workshop,
+        team: Vec<BuddyTemplate>,
+        buddies: &Query<(Entity, &Side), With<Buddy>>,
+    ) {
+        if self.status != OnlineStatus::Shopping {
+            return;
+        }
+        self.send(&ClientMessage::LockIn { team });
+        self.status = OnlineStatus::LockedIn;
+        self.shop_timer = None;
+        workshop.close();
+        for (entity, side) in buddies.iter() {
+            if *side == Side::Shop {
+                commands.entity(entity).despawn_recursive();
+            }
+        }
+    }
+}
+
+/// An [`Online::connect`] running on its own thread, so the menu keeps drawing while the relay
+/// is reached. Inserted by the menu, which starts the game once it has finished.
+pub struct PendingOnline(Mutex<Receiver<io::Result<Online>>>);
+
+impl PendingOnline {
+    pub fn connect(rules: &Rules) -> PendingOnline {
+        let rules = rules.clone();
+        let (sender, result) = mpsc::channel();
+        // an attempt nobody is waiting for anymore is dropped, which hangs up on the relay
+        thread::spawn(move || {
+            let _ = sender.send(Online::connect(&rules));
+        });
+        PendingOnline(Mutex::new(result))
+    }
+
+    /// the connection, or why there isn't one, once the attempt is over
+    pub fn finished(&self) -> Option<io::Result<Online>> {
+        match self.0.lock().unwrap().try_recv() {
+            Ok(result) => Some(result),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => {
+                Some(Err(io::Error::other("the connection attempt stopped")))
+            }
+        }
+    }
+}
+
+impl Drop for Online {
+    fn drop(&mut self) {
+        // lets the reader thread finish, and tells the relay we're gone
+        let _ = self.stream.shutdown(Shutdown::Both);
+    }
+}
+
+#[derive(Component)]
+struct OnlineStatusText;
+
+fn spawn_online_status(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    online: Option<Res<Online>>,
+) {
+    if online.is_none() {
+        return;
+    }
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Auto),
+                position_type: PositionType::Absolute,
+                // ui space is y-up, so this sits at the top of the screen
+                position: Rect {
+                    bottom: Val::Px(20.0),
+                    ..Default::default()
+                },
+                justify_content: JustifyContent::Center,
+                ..Default::default()
+            },
+            color: Color::NONE.into(),
+            ..Default::default()
+        })
+        .insert(FocusPolicy::Pass)
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(TextBundle {
+                    text: Text::with_section(
+                        "",
+                        TextStyle {
+                            font: assets.title_font.clone(),
+                            font_size: 36.0,
+                            color: Color::hex("323232").unwrap(),
+                        },
+                        TextAlignment {
+                            vertical: VerticalAlign::Top,
+                            horizontal: HorizontalAlign::Center,
+                        },
+                    ),
+                    ..Default::default()
+                })
+                .insert(OnlineStatusText)
+                .insert(FocusPolicy::Pass);
+        });
+}
+
+fn receive_relay_messages(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    online: Option<ResMut<Online>>,
+    mut battle: ResMut<Battle>,
+    mut state: ResMut<State<AppState>>,
+    trophies: Res<Trophies>,
+    rules: Res<Rules>,
+    mut run_rng: ResMut<RunRng>,
+    buddies: Query<(Entity, &Side), With<Buddy>>,
+) {
+    let mut online = match online {
+        Some(online) => online,
+        None => return,
+    };
+    if !online.playing() {
+        return;
+    }
+
+    loop {
+        let received = online.inbox.lock().unwrap().try_recv();
+        let message = match received {
+            Ok(message) => message,
+            Err(TryRecvError::Empty) => break,
+            Err(TryRecvError::Disconnected) => {
+                online.status = OnlineStatus::Disconnected;
+                break;
+            }
+        };
+        match message {
+            RelayMessage::Waiting => online.status = OnlineStatus::Waiting,
+            RelayMessage::Matched { seat } => online.seat = seat,
+            RelayMessage::ShopOpen { round, seconds } => {
+                online.round = round;
+                online.shop_timer = Some(Timer::from_seconds(seconds, false));
+                online.status = OnlineStatus::Shopping;
+            }
+            // the battle can only start from the shop, so a fight that turns up while the last
+            // one is still playing out waits for the shop to open again
+            RelayMessage::Fight { left, right } => online.pending_fight = Some((left, right)),
+            RelayMessage::Verified { result } => {
+                online.status = OnlineStatus::Verified(online.seat.result(result));
+            }
+            RelayMessage::Desync => online.status = OnlineStatus::Desync,
+            RelayMessage::OpponentLeft => online.status = OnlineStatus::OpponentLeft,
+        }
+    }
+
+    if *state.current() == AppState::Shop {
+        if let Some((left, right)) = online.pending_fight.take() {
+            // our own team is always on the left, so fight the copy the relay has, in case
+            // anything was moved after locking in
+            let (ours, theirs) = match online.seat {
+                Player::One => (left, right),
+                Player::Two => (right, left),
+            };
+            for (entity, side) in buddies.iter() {
+                if matches!(side, Side::Left | Side::Right) {
+                    commands.entity(entity).despawn_recursive();
+                }
+            }
+            for (slot, template) in ours.into_iter().enumerate() {
+                template.spawn(
+                    &mut commands,
+                    &assets,
+                    slot,
+                    Side::Left,
+                    Transform::default(),
+                );
+            }
+            battle.spawn_opponent(
+                &mut commands,
+                &assets,
+                None,
+                theirs
+                    .into_iter()
+                    .map(|template| (template, None))
+                    .collect(),
+                Transform::from_xyz(900.0, 230.0, 0.0),
+            );
+            online.status = OnlineStatus::Fighting;
+            state.set(AppState::Battle).unwrap();
+        }
+    }
+
+    // the run carries on against the computer, which needs someone to fight this round
+    if !online.playing() && *state.current() == AppState::Shop {
+        let (boss, opponent) =
+            opponent_for_round(&mut run_rng.opponents, &rules, trophies.rounds + 1);
+        battle.spawn_opponent(
+            &mut commands,
+            &assets,
+            boss,
+            opponent,
+            Transform::from_xyz(900.0, 230.0, 0.0),
+        );
+    }
+}
+
+/// Locks the team in for the player once the shop closes
+fn shop_timer(
+    mut commands: Commands,
+    time: Res<Time>,
+    online: Option<ResMut<Online>>,
+    mut workshop: ResMut<Workshop>,
+    team: TeamQuery,
+    buddies: Query<(Entity, &Side), With<Buddy>>,
+) {
+    let mut online = match online {
+        Some(online) => online,
+        None => return,
+    };
+    let finished = match &mut online.shop_timer {
+        Some(timer) => timer.tick(time.delta()).just_finished(),
+        None => false,
+    };
+    if finished {
+        online.lock_in(
+            &mut commands,
+            &mut workshop,
+            team_templates(&team, Side::Left),
+            &buddies,
+        );
+    }
+}
+
+/// Tells the relay how the battle went, so it can check the other client saw the same
+fn report_result(online: Option<Res<Online>>, mut battle_ended_events: EventReader<BattleEnded>) {
+    let online = match online {
+        Some(online) => online,
+        None => return,
+    };
+    for ended in battle_ended_events.iter() {
+        if online.status == OnlineStatus::Fighting {
+            // the relay only deals in results for seat one's team
+            online.send(&ClientMessage::Finished {
+                result: online.seat.result(ended.result),
+            });
+        }
+    }
+}
+
+fn set_online_status_text(
+    online: Option<Res<Online>>,
+    mut texts: Query<&mut Text, With<OnlineStatusText>>,
+) {
+    let online = match online {
+        Some(online) => online,
+        None => return,
+    };
+    let value = match online.status {
+        OnlineStatus::Waiting => "Waiting for an opponent...".to_string(),
+        OnlineStatus::Shopping => {
+            let seconds_left = online.shop_timer.as_ref().map_or(0.0, |timer| {
+                timer.duration().as_secs_f32() - timer.elapsed_secs()
+            });
+            format!(
+                "Round {}: {:.0}s left to shop",
+                online.round,
+                seconds_left.ceil()
+            )
+        }
+        OnlineStatus::LockedIn => "Locked in, waiting for your opponent...".to_string(),
+        OnlineStatus::Fighting => format!("Round {}", online.round),
+        OnlineStatus::Verified(result) => {
+            let result = match result {
+                BattleResult::Win => "You won",
+                BattleResult::Lose => "You lost",
+                BattleResult::Tie => "You tied",
+            };
+            format!(
+                "{} round {}, waiting for the next one...",
+                result, online.round
+            )
+        }
+        OnlineStatus::Desync => {
+            "Your battles didn't match up, so the match is over.\nCarrying on against the computer"
+                .to_string()
+        }
+        OnlineStatus::OpponentLeft => {
+            "Your opponent left.\nCarrying on against the computer".to_string()
+        }
+        OnlineStatus::Disconnected => {
+            "Lost the relay.\nCarrying on against the computer".to_string()
+        }
+    };
+    for mut text in texts.iter_mut() {
+        if text.sections[0].value != value {
+            text.sections[0].value = value.clone();
+        }
+    }
+}
@@ -2,11 +2,14 @@ use crate::{
     game::{
         animate::{AnimateRange, Ease},
         buddy::{Side, Slot},
+        hot_seat::Players,
+        rules::Rules,
         Z_PAD,
     },
+    loading::GameAssets,
     AppState,
 };
-use bevy::prelude::*;
+use bevy::{math::const_vec2, prelude::*};
 use std::time::Duration;
 
 pub const PAD_SPACING: f32 = 180.0;
@@ -15,6 +18,8 @@ const RIGHT_PAD_OUT: f32 = 1500.0;
 const PAD_CENTER_OFFSET: f32 = ((Slot::MAX_PER_SIDE - 1) as f32 * PAD_SPACING) / 2.0;
 const SHOP_PAD_OFFSET: f32 = -200.0;
 const SHOP_PAD_OUT: f32 = -800.0;
+const PREVIEW_OFFSET: Vec2 = const_vec2!([330.0, 230.0]);
+const PREVIEW_SCALE: f32 = 0.45;
 
 #[derive(Bundle, Default)]
 pub struct PadBundle {
@@ -81,14 +86,14 @@ impl Default for Pad {
     }
 }
 
-pub fn spawn_pads(commands: &mut Commands, asset_server: &AssetServer) {
+pub fn spawn_pads(commands: &mut Commands, assets: &GameAssets) {
     for i in 0..Slot::MAX_PER_SIDE {
-        spawn_pad(commands, asset_server, Side::Left, Slot::new(i));
-        spawn_pad(commands, asset_server, Side::Right, Slot::new(i));
+        spawn_pad(commands, assets, Side::Left, Slot::new(i));
+        spawn_pad(commands, assets, Side::Right, Slot::new(i));
     }
 }
 
-pub fn spawn_pad(commands: &mut Commands, asset_server: &AssetServer, side: Side, slot: Slot) {
+pub fn spawn_pad(commands: &mut Commands, assets: &GameAssets, side: Side, slot: Slot) {
     commands
         .spawn_bundle(PadBundle {
             side,
@@ -97,7 +102,7 @@ pub fn spawn_pad(commands: &mut Commands, asset_server: &AssetServer, side: Side
         })
         .with_children(|parent| {
             parent.spawn_bundle(SpriteBundle {
-                texture: asset_server.load("pad.png"),
+                texture: assets.pad.clone(),
                 transform: Transform::from_xyz(0., -60., Z_PAD),
                 ..Default::default()
             });
@@ -107,9 +112,40 @@ pub fn spawn_pad(commands: &mut Commands, asset_server: &AssetServer, side: Side
 pub fn position_pad(
     time: Res<Time>,
     state: Res<State<AppState>>,
+    rules: Res<Rules>,
+    players: Res<Players>,
     mut pads: Query<(&mut Pad, &mut Transform, &Side, &Slot)>,
 ) {
+    let shopping = *state.current() != AppState::Battle;
+    // the shop row is centered, however many buddies it has
+    let shop_offset = (rules.shop_slots() - 1) as f32 * PAD_SPACING / 2.0;
     for (mut pad, mut transform, side, slot) in pads.iter_mut() {
+        if players.owns_both_teams() && shopping && *side != Side::Shop {
+            // the player whose turn it is shops where the left team normally does, and the
+            // other player's team waits off screen so it can't be peeked at (in the sandbox,
+            // the team being edited takes the middle the same way)
+            let slot_x = slot.current as f32 * PAD_SPACING;
+            let x = match (*side, *side == players.turn.side()) {
+                (Side::Left, true) => PAD_CENTER_OFFSET - slot_x,
+                (Side::Left, false) => -RIGHT_PAD_OUT - slot_x,
+                // the right team faces the other way, so its front is on the left
+                (_, true) => slot_x - PAD_CENTER_OFFSET,
+                (_, false) => RIGHT_PAD_OUT + slot_x,
+            };
+            *transform = Transform::from_xyz(x, 0.0, 0.0);
+            continue;
+        }
+        if *side == Side::Right && shopping && rules.opponent_preview {
+            // the next opponent waits, shrunk down, in the top right corner of the shop
+            let position =
+                PREVIEW_OFFSET + Vec2::new(slot.current as f32 * PAD_SPACING * PREVIEW_SCALE, 0.0);
+            *transform = Transform::from_translation(position.extend(0.0)).with_scale(Vec3::new(
+                PREVIEW_SCALE,
+                PREVIEW_SCALE,
+                1.0,
+            ));
+            continue;
+        }
         let side_sign;
         let offset = match side {
             Side::Left => {
@@ -131,9 +167,9 @@ pub fn position_pad(
             Side::Shop => {
                 side_sign = -1.0;
                 if *state.current() == AppState::Battle {
-                    Vec2::new(PAD_CENTER_OFFSET, pad.shop_animate_out.tick(time.delta()))
+                    Vec2::new(shop_offset, pad.shop_animate_out.tick(time.delta()))
                 } else {
-                    Vec2::new(PAD_CENTER_OFFSET, pad.shop_animate_in.tick(time.delta()))
+                    Vec2::new(shop_offset, pad.shop_animate_in.tick(time.delta()))
                 }
             }
         };
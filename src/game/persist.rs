@@ -0,0 +1,84 @@
+use bevy::prelude::*;
+use serde::{de::DeserializeOwned, Serialize};
+use std::{fs, path::PathBuf};
+
+const SAVE_DIR: &str = "save";
+
+fn save_path(name: &str) -> PathBuf {
+    PathBuf::from(SAVE_DIR).join(format!("{}.ron", name))
+}
+
+/// Loads a previously saved value, falling back to the default when nothing has been saved yet
+/// (or when the platform has no filesystem, like wasm)
+pub fn load<T: DeserializeOwned + Default>(name: &str) -> T {
+    try_load(name).unwrap_or_default()
+}
+
+pub fn try_load<T: DeserializeOwned>(name: &str) -> Option<T> {
+    let contents = fs::read_to_string(save_path(name)).ok()?;
+    match ron::from_str(&contents) {
+        Ok(value) => Some(value),
+        Err(err) => {
+            warn!("failed to parse save file {}: {}", name, err);
+            None
+        }
+    }
+}
+
+/// The names of every save starting with `prefix`
+pub fn names(prefix: &str) -> Vec<String> {
+    let entries = match fs::read_dir(SAVE_DIR) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+    entries
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            if path.extension()? != "ron" {
+                return None;
+            }
+            let name = path.file_stem()?.to_str()?;
+            name.starts_with(prefix).then(|| name.to_string())
+        })
+        .collect()
+}
+
+pub fn exists(name: &str) -> bool {
+    save_path(name).exists()
+}
+
+pub fn delete(name: &str) {
+    if exists(name) {
+        if let Err(err) = fs::remove_file(save_path(name)) {
+            warn!("failed to delete save file {}: {}", name, err);
+        }
+    }
+}
+
+pub fn save<T: Serialize>(name: &str, value: &T) {
+    let contents = match ron::ser::to_string_pretty(value, Default::default()) {
+        Ok(contents) => contents,
+        Err(err) => {
+            warn!("failed to serialize {}: {}", name, err);
+            return;
+        }
+    };
+    if let Err(err) =
+        fs::create_dir_all(SAVE_DIR).and_then(|_| fs::write(save_path(name), contents))
+    {
+        warn!("failed to write save file {}: {}", name, err);
+    }
+}
+
+/// Writes a plain text file next to the saves, for players to read or share outside the game.
+/// Returns where it was written.
+pub fn export_text(file_name: &str, contents: &str) -> Option<PathBuf> {
+    let path = PathBuf::from(SAVE_DIR).join(file_name);
+    match fs::create_dir_all(SAVE_DIR).and_then(|_| fs::write(&path, contents)) {
+        Ok(()) => Some(path),
+        Err(err) => {
+            warn!("failed to write {}: {}", file_name, err);
+            None
+        }
+    }
+}
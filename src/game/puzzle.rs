@@ -0,0 +1,465 @@
+use crate::{
+    game::{
+        boss::BossAbility,
+        buddy::{Buddy, BuddyColor, BuddyFace, BuddyTemplate, Side},
+        counters::{Coins, Trophies},
+        events::{BattleEnded, BattleResult, BuddyFainted},
+        hot_seat::Player,
+        persist, return_to_menu,
+        rules::Rules,
+        shop::{add_price, BUDDY_PRICE, STARTING_COINS},
+        BattleMessages, GameEntities,
+    },
+    loading::GameAssets,
+    menu::{HOVERED_BUTTON, NORMAL_BUTTON},
+    AppState,
+};
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::TypeUuid,
+    ui::FocusPolicy,
+    utils::BoxedFuture,
+};
+use rand::{rngs::StdRng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+/// Handmade scenarios with a fixed shop, a fixed enemy team, a coin budget and a goal. Each one
+/// unlocks once the one before it is solved.
+pub struct PuzzlePlugin;
+
+impl Plugin for PuzzlePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(persist::load::<PuzzleProgress>(PuzzleProgress::SAVE_NAME))
+            .add_system_set(SystemSet::on_enter(AppState::Startup).with_system(spawn_puzzle_banner))
+            .add_system_set(SystemSet::on_enter(AppState::Shop).with_system(start_attempt))
+            .add_system_set(
+                SystemSet::on_update(AppState::Shop)
+                    .with_system(puzzle_buttons)
+                    .with_system(set_puzzle_banner_text),
+            )
+            .add_system_set(SystemSet::on_enter(AppState::Battle).with_system(count_coins_left))
+            .add_system_set(SystemSet::on_update(AppState::Battle).with_system(judge_attempt))
+            .add_system_set(SystemSet::on_enter(AppState::Menu).with_system(leave_puzzle));
+    }
+}
+
+/// Every puzzle, in the order they unlock. Progress is kept by path, so puzzles can be added
+/// in between without losing track of what was solved.
+pub const PUZZLES: &[&str] = &[
+    "puzzles/01_first_steps.puzzle",
+    "puzzles/02_color_wheel.puzzle",
+    "puzzles/03_penny_pincher.puzzle",
+    "puzzles/04_not_a_scratch.puzzle",
+    "puzzles/05_shield_wall.puzzle",
+];
+
+/// A puzzle file, written in RON
+#[derive(Clone, Deserialize, TypeUuid)]
+#[uuid = "8f0c2b6e-4d1a-4e7b-9a35-61c7d2e0b9f4"]
+pub struct Puzzle {
+    pub name: String,
+    /// shown while shopping, until the first attempt
+    #[serde(default)]
+    pub hint: String,
+    pub coins: usize,
+    pub goal: Goal,
+    /// the player's own rules don't apply, so a puzzle plays the same for everyone
+    #[serde(default)]
+    pub rules: Rules,
+    /// the shop, in slot order
+    pub shop: Vec<PuzzleBuddy>,
+    /// the enemy team, front to back
+    pub enemy: Vec<PuzzleBuddy>,
+}
+
+impl Puzzle {
+    /// the puzzle's rules, with a shop pad for every buddy on offer
+    pub fn rules(&self) -> Rules {
+        let mut rules = self.rules.clone();
+        rules.shop_size = Some(self.shop.len().max(1));
+        rules
+    }
+}
+
+#[derive(Clone, Deserialize)]
+pub struct PuzzleBuddy {
+    pub color: BuddyColor,
+    #[serde(default)]
+    pub face: BuddyFace,
+    pub health: usize,
+    pub strength: usize,
+    /// only used in the shop
+    #[serde(default = "PuzzleBuddy::default_price")]
+    pub price: usize,
+}
+
+impl PuzzleBuddy {
+    fn default_price() -> usize {
+        BUDDY_PRICE
+    }
+
+    fn template(&self, rng: &mut StdRng) -> BuddyTemplate {
+        BuddyTemplate::random_for_round(rng, 1)
+            .with_stats(self.health, self.strength)
+            .with_color(self.color)
+            .with_face(self.face)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize)]
+pub enum Goal {
+    Win,
+    /// win with at least this many coins of the budget left unspent
+    WinWithCoins(usize),
+    /// win without a buddy on the team fainting
+    WinWithoutLosses,
+}
+
+impl Goal {
+    pub fn description(&self) -> String {
+        match self {
+            Goal::Win => "Win the battle".to_string(),
+            Goal::WinWithCoins(coins) => format!("Win with {} coins left over", coins),
+            Goal::WinWithoutLosses => "Win without losing a buddy".to_string(),
+        }
+    }
+
+    fn met(&self, result: BattleResult, coins_left: usize, lost_buddy: bool) -> bool {
+        result == BattleResult::Win
+            && match self {
+                Goal::Win => true,
+                Goal::WinWithCoins(coins) => coins_left >= *coins,
+                Goal::WinWithoutLosses => !lost_buddy,
+            }
+    }
+}
+
+#[derive(Default)]
+pub struct PuzzleLoader;
+
+impl AssetLoader for PuzzleLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let puzzle = ron::de::from_bytes::<Puzzle>(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(puzzle));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["puzzle"]
+    }
+}
+
+/// The puzzles solved so far, by path
+#[derive(Default, Serialize, Deserialize)]
+pub struct PuzzleProgress {
+    solved: Vec<String>,
+}
+
+impl PuzzleProgress {
+    const SAVE_NAME: &'static str = "puzzle_progress";
+
+    pub fn is_solved(&self, index: usize) -> bool {
+        self.solved.iter().any(|path| path == PUZZLES[index])
+    }
+
+    pub fn is_unlocked(&self, index: usize) -> bool {
+        index == 0 || self.is_solved(index - 1)
+    }
+
+    fn solve(&mut self, index: usize) {
+        if !self.is_solved(index) {
+            self.solved.push(PUZZLES[index].to_string());
+        }
+    }
+}
+
+/// Inserted by the menu when starting a puzzle. Every visit to the shop is a fresh attempt.
+pub struct PuzzleRun {
+    pub index: usize,
+    pub puzzle: Puzzle,
+    /// the coins that were still unspent when the battle started
+    coins_left: usize,
+    lost_buddy: bool,
+    /// whether the last attempt met the goal, None before the first one
+    solved: Option<bool>,
+}
+
+impl PuzzleRun {
+    pub fn new(index: usize, puzzle: Puzzle) -> Self {
+        Self {
+            index,
+            puzzle,
+            coins_left: 0,
+            lost_buddy: false,
+            solved: None,
+        }
+    }
+
+    /// Names and looks are rolled from the puzzle's place in the list, so every attempt meets
+    /// the same buddies
+    fn templates(&self, buddies: &[PuzzleBuddy], seed: u64) -> Vec<BuddyTemplate> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        buddies
+            .iter()
+            .map(|buddy| buddy.template(&mut rng))
+            .collect()
+    }
+
+    pub fn enemy(&self) -> Vec<(BuddyTemplate, Option<BossAbility>)> {
+        self.templates(&self.puzzle.enemy, !(self.index as u64))
+            .into_iter()
+            .map(|template| (template, None))
+            .collect()
+    }
+
+    /// Replaces whatever is left in the shop with the puzzle's stock, at the puzzle's prices
+    pub fn stock_shop(
+        &self,
+        commands: &mut Commands,
+        assets: &GameAssets,
+        buddies: &Query<(Entity, &Side), With<Buddy>>,
+    ) {
+        for (entity, side) in buddies.iter() {
+            if *side == Side::Shop {
+                commands.entity(entity).despawn_recursive();
+            }
+        }
+
+        let templates = self.templates(&self.puzzle.shop, self.index as u64);
+        for (slot, (template, buddy)) in templates.into_iter().zip(&self.puzzle.shop).enumerate() {
+            let entity = template.spawn(
+                commands,
+                assets,
+                slot,
+                Side::Shop,
+                Transform::from_xyz(0.0, -500.0, 0.0),
+            );
+            add_price(commands, assets, entity, buddy.price);
+        }
+    }
+}
+
+/// The puzzle's title, goal and how the last attempt went, along the top of the screen
+struct PuzzleBanner(Entity);
+
+#[derive(Component)]
+struct PuzzleBannerText;
+
+#[derive(Component)]
+struct LeavePuzzleButton;
+
+fn spawn_puzzle_banner(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    puzzle: Option<Res<PuzzleRun>>,
+) {
+    if puzzle.is_none() {
+        return;
+    }
+    let font = assets.title_font.clone();
+    let banner = commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Auto),
+                position_type: PositionType::Absolute,
+                // ui space is y-up, so this sits at the top of the screen
+                position: Rect {
+                    bottom: Val::Px(20.0),
+                    ..Default::default()
+                },
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            color: Color::NONE.into(),
+            ..Default::default()
+        })
+        .insert(FocusPolicy::Pass)
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(TextBundle {
+                    text: Text::with_section(
+                        "",
+                        TextStyle {
+                            font: font.clone(),
+                            font_size: 36.0,
+                            color: Color::hex("323232").unwrap(),
+                        },
+                        TextAlignment {
+                            vertical: VerticalAlign::Top,
+                            horizontal: HorizontalAlign::Center,
+                        },
+                    ),
+                    ..Default::default()
+                })
+                .insert(PuzzleBannerText)
+                .insert(FocusPolicy::Pass);
+            parent
+                .spawn_bundle(ButtonBundle {
+                    style: Style {
+                        size: Size::new(Val::Px(130.0), Val::Px(40.0)),
+                        margin: Rect {
+                            left: Val::Px(16.0),
+                            ..Default::default()
+                        },
+                        // horizontally center child text
+                        justify_content: JustifyContent::Center,
+                        // vertically center child text
+                        align_items: AlignItems::Center,
+                        ..Default::default()
+                    },
+                    color: NORMAL_BUTTON.into(),
+                    ..Default::default()
+                })
+                .insert(LeavePuzzleButton)
+                .with_children(|parent| {
+                    parent.spawn_bundle(TextBundle {
+                        text: Text::with_section(
+                            "Puzzles",
+                            TextStyle {
+                                font,
+                                font_size: 30.0,
+                                color: Color::rgb(0.9, 0.9, 0.9),
+                            },
+                            Default::default(),
+                        ),
+                        ..Default::default()
+                    });
+                });
+        })
+        .id();
+    commands.insert_resource(PuzzleBanner(banner));
+}
+
+/// Hands out the budget again and clears the team, so every visit to the shop starts the
+/// puzzle over
+fn start_attempt(
+    mut commands: Commands,
+    puzzle: Option<ResMut<PuzzleRun>>,
+    mut coins: ResMut<Coins>,
+    buddies: Query<(Entity, &Side), With<Buddy>>,
+) {
+    let mut puzzle = match puzzle {
+        Some(puzzle) => puzzle,
+        None => return,
+    };
+    coins[Player::One] = puzzle.puzzle.coins;
+    puzzle.lost_buddy = false;
+    for (entity, side) in buddies.iter() {
+        if *side == Side::Left {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+fn count_coins_left(puzzle: Option<ResMut<PuzzleRun>>, coins: Res<Coins>) {
+    if let Some(mut puzzle) = puzzle {
+        puzzle.coins_left = coins[Player::One];
+    }
+}
+
+fn judge_attempt(
+    puzzle: Option<ResMut<PuzzleRun>>,
+    mut progress: ResMut<PuzzleProgress>,
+    mut fainted_events: EventReader<BuddyFainted>,
+    mut battle_ended_events: EventReader<BattleEnded>,
+) {
+    let mut puzzle = match puzzle {
+        Some(puzzle) => puzzle,
+        None => return,
+    };
+    if fainted_events
+        .iter()
+        .any(|fainted| fainted.side == Side::Left)
+    {
+        puzzle.lost_buddy = true;
+    }
+    if let Some(ended) = battle_ended_events.iter().last() {
+        let solved = puzzle
+            .puzzle
+            .goal
+            .met(ended.result, puzzle.coins_left, puzzle.lost_buddy);
+        puzzle.solved = Some(solved);
+        if solved && !progress.is_solved(puzzle.index) {
+            progress.solve(puzzle.index);
+            persist::save(PuzzleProgress::SAVE_NAME, &*progress);
+        }
+    }
+}
+
+fn set_puzzle_banner_text(
+    puzzle: Option<Res<PuzzleRun>>,
+    mut texts: Query<&mut Text, With<PuzzleBannerText>>,
+) {
+    let puzzle = match puzzle {
+        Some(puzzle) if puzzle.is_changed() => puzzle,
+        _ => return,
+    };
+    let status = match puzzle.solved {
+        None => puzzle.puzzle.hint.clone(),
+        Some(true) if puzzle.index + 1 < PUZZLES.len() => {
+            "Solved! The next puzzle is unlocked".to_string()
+        }
+        Some(true) => "Solved! That was the last puzzle".to_string(),
+        Some(false) => "Not quite, try again".to_string(),
+    };
+    let value = format!(
+        "Puzzle {}: {}\n{} with {} coins\n{}",
+        puzzle.index + 1,
+        puzzle.puzzle.name,
+        puzzle.puzzle.goal.description(),
+        puzzle.puzzle.coins,
+        status
+    );
+    for mut text in texts.iter_mut() {
+        text.sections[0].value = value.clone();
+    }
+}
+
+fn puzzle_buttons(
+    mut state: ResMut<State<AppState>>,
+    mut interactions: Query<
+        (&Interaction, &mut UiColor),
+        (Changed<Interaction>, With<LeavePuzzleButton>),
+    >,
+) {
+    for (interaction, mut color) in interactions.iter_mut() {
+        match *interaction {
+            Interaction::Clicked => state.set(AppState::Menu).unwrap(),
+            Interaction::Hovered => *color = HOVERED_BUTTON.into(),
+            Interaction::None => *color = NORMAL_BUTTON.into(),
+        }
+    }
+}
+
+/// The puzzle's budget and rules are swapped back for the usual ones on the way out
+fn leave_puzzle(
+    mut commands: Commands,
+    puzzle: Option<Res<PuzzleRun>>,
+    banner: Option<Res<PuzzleBanner>>,
+    battle_messages: Option<Res<BattleMessages>>,
+    mut coins: ResMut<Coins>,
+    mut trophies: ResMut<Trophies>,
+    entities: GameEntities,
+) {
+    if puzzle.is_none() {
+        return;
+    }
+    commands.remove_resource::<PuzzleRun>();
+    commands.insert_resource(Rules::saved());
+    *coins = Coins::new(STARTING_COINS);
+    *trophies = Trophies::default();
+
+    if let Some(banner) = banner {
+        commands.entity(banner.0).despawn_recursive();
+        commands.remove_resource::<PuzzleBanner>();
+    }
+    return_to_menu(&mut commands, &entities, battle_messages);
+}
@@ -0,0 +1,300 @@
+use crate::{
+    game::{
+        battle::Battle,
+        boss::{Boss, BossAbility},
+        buddy::{BuddyTemplate, Side},
+        events::{BattleEnded, BattleResult},
+        persist, return_to_menu,
+        rules::Rules,
+        ui::UiRoot,
+        BattleMessages, GameEntities,
+    },
+    loading::GameAssets,
+    AppState,
+};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Records every battle so it can be watched again from the replays screen
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Recording>()
+            .add_system(save_replay)
+            .add_system_set(SystemSet::on_enter(AppState::Startup).with_system(start_playback))
+            .add_system_set(
+                SystemSet::on_enter(AppState::Battle).with_system(spawn_playback_controls),
+            )
+            .add_system_set(SystemSet::on_update(AppState::Battle).with_system(playback_controls))
+            .add_system_set(SystemSet::on_exit(AppState::Battle).with_system(finish_playback));
+    }
+}
+
+/// Everything needed to fight a battle again: both starting teams and the rules
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Replay {
+    /// counts up with every recorded battle, so replays can be listed newest first
+    pub number: usize,
+    /// the [`Rules::VERSION`] the battle was fought under
+    pub rules_version: u32,
+    pub rules: Rules,
+    pub round: usize,
+    pub boss: Option<Boss>,
+    /// team buddies in slot order
+    pub left: Vec<BuddyTemplate>,
+    /// opposing buddies in slot order
+    pub right: Vec<(BuddyTemplate, Option<BossAbility>)>,
+    /// replays are only saved once the battle is over
+    pub result: BattleResult,
+}
+
+impl Replay {
+    const MAX_REPLAYS: usize = 10;
+    const SAVE_PREFIX: &'static str = "replay_";
+
+    fn save_name(number: usize) -> String {
+        format!("{}{}", Self::SAVE_PREFIX, number)
+    }
+
+    /// the number of every saved replay, going by the file names
+    fn numbers() -> Vec<usize> {
+        persist::names(Self::SAVE_PREFIX)
+            .iter()
+            .filter_map(|name| name.strip_prefix(Self::SAVE_PREFIX)?.parse().ok())
+            .collect()
+    }
+
+    /// the most recent replays, newest first
+    pub fn load_all() -> Vec<Replay> {
+        let mut numbers = Self::numbers();
+        numbers.sort_unstable_by(|a, b| b.cmp(a));
+        numbers
+            .into_iter()
+            .take(Self::MAX_REPLAYS)
+            .filter_map(Self::load)
+            .collect()
+    }
+
+    pub fn load(number: usize) -> Option<Replay> {
+        persist::try_load::<Replay>(&Self::save_name(number))
+    }
+
+    pub fn next_number() -> usize {
+        Self::numbers()
+            .into_iter()
+            .max()
+            .map_or(0, |number| number + 1)
+    }
+
+    /// the oldest replays are deleted once there are too many
+    fn save(&self) {
+        persist::save(&Self::save_name(self.number), self);
+        for number in Self::numbers() {
+            if number + Self::MAX_REPLAYS <= self.number {
+                persist::delete(&Self::save_name(number));
+            }
+        }
+    }
+
+    pub fn label(&self) -> String {
+        let mut label = format!(
+            "#{}  Round {}: {:?}",
+            self.number + 1,
+            self.round,
+            self.result
+        );
+        if let Some(boss) = self.boss {
+            label.push_str(&format!(" vs {}", boss.title()));
+        }
+        if self.rules_version != Rules::VERSION {
+            label.push_str(" (old rules)");
+        }
+        label
+    }
+}
+
+/// The replay of the battle currently being fought, saved once it ends
+#[derive(Default)]
+pub struct Recording(pub Option<Replay>);
+
+fn save_replay(
+    mut recording: ResMut<Recording>,
+    mut battle_ended_events: EventReader<BattleEnded>,
+) {
+    for ended in battle_ended_events.iter() {
+        if let Some(mut replay) = recording.0.take() {
+            replay.result = ended.result;
+            replay.save();
+        }
+    }
+}
+
+/// Set by the menu to watch a replay instead of starting a run
+pub struct ReplayPlayback {
+    pub replay: Replay,
+    paused: bool,
+    speed: usize,
+    /// run until the next battle action, then pause again
+    step: bool,
+}
+
+impl ReplayPlayback {
+    const SPEEDS: &'static [f32] = &[0.25, 0.5, 1.0, 2.0, 4.0];
+    const NORMAL_SPEED: usize = 2;
+
+    pub fn new(replay: Replay) -> Self {
+        Self {
+            replay,
+            paused: false,
+            speed: Self::NORMAL_SPEED,
+            step: false,
+        }
+    }
+
+    /// how much battle time passes for a frame that took `delta`
+    pub fn delta(&self, delta: Duration) -> Duration {
+        if self.paused && !self.step {
+            Duration::ZERO
+        } else if self.step {
+            delta
+        } else {
+            delta.mul_f32(Self::SPEEDS[self.speed])
+        }
+    }
+
+    pub fn action_finished(&mut self) {
+        self.step = false;
+    }
+
+    fn status(&self) -> String {
+        let state = if self.paused { "Paused" } else { "Playing" };
+        format!(
+            "Replay {}: {} x{}    Space: pause   Right: step   Up/Down: speed   Esc: leave",
+            self.replay.number + 1,
+            state,
+            Self::SPEEDS[self.speed]
+        )
+    }
+}
+
+/// Spawns the recorded teams. The game goes straight to the battle when there is a replay to
+/// watch.
+fn start_playback(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    mut battle: ResMut<Battle>,
+    playback: Option<Res<ReplayPlayback>>,
+) {
+    let replay = match playback {
+        Some(playback) => playback.replay.clone(),
+        None => return,
+    };
+    for (slot, template) in replay.left.into_iter().enumerate() {
+        template.spawn(
+            &mut commands,
+            &assets,
+            slot,
+            Side::Left,
+            Transform::default(),
+        );
+    }
+    battle.spawn_opponent(
+        &mut commands,
+        &assets,
+        replay.boss,
+        replay.right,
+        Transform::default(),
+    );
+}
+
+#[derive(Component)]
+struct PlaybackStatus;
+
+fn spawn_playback_controls(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    playback: Option<Res<ReplayPlayback>>,
+    ui_root: Query<Entity, With<UiRoot>>,
+) {
+    let playback = match playback {
+        Some(playback) => playback,
+        None => return,
+    };
+    commands.entity(ui_root.single()).with_children(|parent| {
+        parent
+            .spawn_bundle(TextBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    // ui space is y-up, so this sits at the top of the screen
+                    position: Rect {
+                        bottom: Val::Px(20.0),
+                        left: Val::Px(20.0),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                text: Text::with_section(
+                    playback.status(),
+                    TextStyle {
+                        font: assets.title_font.clone(),
+                        font_size: 34.0,
+                        color: Color::hex("323232").unwrap(),
+                    },
+                    Default::default(),
+                ),
+                ..Default::default()
+            })
+            .insert(PlaybackStatus);
+    });
+}
+
+fn playback_controls(
+    keyboard: Res<Input<KeyCode>>,
+    mut state: ResMut<State<AppState>>,
+    playback: Option<ResMut<ReplayPlayback>>,
+    mut status: Query<&mut Text, With<PlaybackStatus>>,
+) {
+    let mut playback = match playback {
+        Some(playback) => playback,
+        None => return,
+    };
+    if keyboard.just_pressed(KeyCode::Space) {
+        playback.paused = !playback.paused;
+    }
+    if keyboard.just_pressed(KeyCode::Right) {
+        playback.paused = true;
+        playback.step = true;
+    }
+    if keyboard.just_pressed(KeyCode::Up) {
+        playback.speed = (playback.speed + 1).min(ReplayPlayback::SPEEDS.len() - 1);
+    }
+    if keyboard.just_pressed(KeyCode::Down) {
+        playback.speed = playback.speed.saturating_sub(1);
+    }
+    if keyboard.just_pressed(KeyCode::Escape) {
+        // the battle may already be heading back to the menu this frame
+        let _ = state.overwrite_set(AppState::Menu);
+    }
+
+    if playback.is_changed() {
+        for mut text in status.iter_mut() {
+            text.sections[0].value = playback.status();
+        }
+    }
+}
+
+/// Leaving a replay goes back to the menu, not the shop
+fn finish_playback(
+    mut commands: Commands,
+    playback: Option<Res<ReplayPlayback>>,
+    battle_messages: Option<Res<BattleMessages>>,
+    entities: GameEntities,
+) {
+    if playback.is_none() {
+        return;
+    }
+    commands.remove_resource::<ReplayPlayback>();
+    return_to_menu(&mut commands, &entities, battle_messages);
+}
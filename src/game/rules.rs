@@ -0,0 +1,103 @@
+use crate::game::{
+    buddy::{BuddyColor, BuddyTemplate},
+    daily::DailyChallenge,
+    persist,
+    puzzle::PuzzleRun,
+    shop::SHOP_BUDDY_SLOTS,
+};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Optional gameplay rules that can be switched on and off from the settings screen
+pub struct RulesPlugin;
+
+impl Plugin for RulesPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Rules::saved()).add_system(save_rules);
+    }
+}
+
+// new rules should default to off, so missing fields in old saves keep the old behavior
+#[derive(Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Rules {
+    /// red beats green, green beats blue, blue beats red
+    pub color_advantage: bool,
+    /// the next opponent is shown while shopping
+    pub opponent_preview: bool,
+    /// only buddies of this color turn up, in the shop and on the other side
+    pub only_color: Option<BuddyColor>,
+    /// buddies offered in the shop every round, instead of the usual number
+    pub shop_size: Option<usize>,
+    /// battles pay out twice the coins
+    pub double_rewards: bool,
+}
+
+impl Rules {
+    const SAVE_NAME: &'static str = "rules";
+    /// bump whenever battles resolve differently, so old replays can be told apart
+    pub const VERSION: u32 = 1;
+
+    /// the rules as the player last set them
+    pub fn saved() -> Rules {
+        persist::load(Self::SAVE_NAME)
+    }
+
+    pub fn shop_slots(&self) -> usize {
+        self.shop_size.unwrap_or(SHOP_BUDDY_SLOTS)
+    }
+
+    /// a freshly rolled buddy, changed to fit these rules
+    pub fn restyle(&self, template: BuddyTemplate) -> BuddyTemplate {
+        match self.only_color {
+            Some(color) => template.with_color(color),
+            None => template,
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum Rule {
+    ColorAdvantage,
+    OpponentPreview,
+}
+
+impl Rule {
+    pub const ALL: &'static [Rule] = &[Rule::ColorAdvantage, Rule::OpponentPreview];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Rule::ColorAdvantage => "Color advantage",
+            Rule::OpponentPreview => "Opponent preview",
+        }
+    }
+
+    pub fn enabled(&self, rules: &Rules) -> bool {
+        match self {
+            Rule::ColorAdvantage => rules.color_advantage,
+            Rule::OpponentPreview => rules.opponent_preview,
+        }
+    }
+
+    pub fn enabled_mut<'a>(&self, rules: &'a mut Rules) -> &'a mut bool {
+        match self {
+            Rule::ColorAdvantage => &mut rules.color_advantage,
+            Rule::OpponentPreview => &mut rules.opponent_preview,
+        }
+    }
+}
+
+fn save_rules(
+    rules: Res<Rules>,
+    daily: Option<Res<DailyChallenge>>,
+    puzzle: Option<Res<PuzzleRun>>,
+) {
+    // daily challenges and puzzles play under rules of their own, which shouldn't replace the
+    // player's
+    if daily.is_some() || puzzle.is_some() {
+        return;
+    }
+    if rules.is_changed() && !rules.is_added() {
+        persist::save(Rules::SAVE_NAME, &*rules);
+    }
+}
@@ -0,0 +1,28 @@
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+/// The random numbers behind a run's shops and opponents. Each has a generator of its own, so how
+/// often one of them has been rolled doesn't change what the other turns up. Runs started from
+/// the same seed (like a daily challenge) get the same shops and opponents. These are ChaCha
+/// generators rather than `StdRng`, which is free to change what it rolls between `rand`
+/// versions.
+pub struct RunRng {
+    pub shop: ChaCha8Rng,
+    pub opponents: ChaCha8Rng,
+}
+
+impl RunRng {
+    pub fn from_seed(seed: u64) -> Self {
+        Self {
+            shop: ChaCha8Rng::seed_from_u64(seed),
+            // any other seed would do, as long as it's derived from this one
+            opponents: ChaCha8Rng::seed_from_u64(!seed),
+        }
+    }
+}
+
+impl Default for RunRng {
+    fn default() -> Self {
+        Self::from_seed(rand::thread_rng().gen())
+    }
+}
@@ -0,0 +1,190 @@
+use crate::{
+    game::{
+        buddy::{Buddy, BuddyColor, BuddyTemplate, Side, Slot},
+        hot_seat::Players,
+        run_rng::RunRng,
+        shop::BuddyDragState,
+        workshop::Workshop,
+    },
+    loading::GameAssets,
+    menu::{HOVERED_BUTTON, NORMAL_BUTTON},
+    AppState,
+};
+use bevy::{prelude::*, ui::FocusPolicy};
+
+/// Building both teams by hand to try out a matchup. The shop always has a plain buddy of every
+/// color for free, the workshop edits stats for free, and a switch picks the team being edited.
+pub struct SandboxPlugin;
+
+impl Plugin for SandboxPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_enter(AppState::Shop).with_system(spawn_side_switch))
+            .add_system_set(
+                SystemSet::on_update(AppState::Shop)
+                    .with_system(stock_palette)
+                    .with_system(switch_side)
+                    .with_system(set_side_switch_text),
+            )
+            .add_system_set(SystemSet::on_exit(AppState::Shop).with_system(despawn_side_switch));
+    }
+}
+
+struct SideSwitch(Entity);
+
+#[derive(Component)]
+struct SideSwitchButton;
+
+#[derive(Component)]
+struct SideSwitchText;
+
+fn spawn_side_switch(mut commands: Commands, assets: Res<GameAssets>, players: Res<Players>) {
+    if !players.sandbox {
+        return;
+    }
+    let font = assets.title_font.clone();
+    let bar = commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Auto),
+                position_type: PositionType::Absolute,
+                // ui space is y-up, so this sits at the top of the screen
+                position: Rect {
+                    bottom: Val::Px(20.0),
+                    ..Default::default()
+                },
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            color: Color::NONE.into(),
+            ..Default::default()
+        })
+        .insert(FocusPolicy::Pass)
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(TextBundle {
+                    text: Text::with_section(
+                        "",
+                        TextStyle {
+                            font: font.clone(),
+                            font_size: 44.0,
+                            color: Color::hex("323232").unwrap(),
+                        },
+                        Default::default(),
+                    ),
+                    ..Default::default()
+                })
+                .insert(SideSwitchText)
+                .insert(FocusPolicy::Pass);
+            parent
+                .spawn_bundle(ButtonBundle {
+                    style: Style {
+                        size: Size::new(Val::Px(130.0), Val::Px(40.0)),
+                        margin: Rect {
+                            left: Val::Px(16.0),
+                            ..Default::default()
+                        },
+                        // horizontally center child text
+                        justify_content: JustifyContent::Center,
+                        // vertically center child text
+                        align_items: AlignItems::Center,
+                        ..Default::default()
+                    },
+                    color: NORMAL_BUTTON.into(),
+                    ..Default::default()
+                })
+                .insert(SideSwitchButton)
+                .with_children(|parent| {
+                    parent.spawn_bundle(TextBundle {
+                        text: Text::with_section(
+                            "Switch",
+                            TextStyle {
+                                font,
+                                font_size: 30.0,
+                                color: Color::rgb(0.9, 0.9, 0.9),
+                            },
+                            Default::default(),
+                        ),
+                        ..Default::default()
+                    });
+                });
+        })
+        .id();
+    commands.insert_resource(SideSwitch(bar));
+}
+
+/// Puts a fresh 1/1 buddy back on a color's pad as soon as the last one is taken
+fn stock_palette(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    players: Res<Players>,
+    mut run_rng: ResMut<RunRng>,
+    buddies: Query<(&Side, &Slot), With<Buddy>>,
+) {
+    if !players.sandbox {
+        return;
+    }
+    for (index, color) in BuddyColor::COLORS.iter().enumerate() {
+        if buddies
+            .iter()
+            .any(|(side, slot)| *side == Side::Shop && slot.base == index)
+        {
+            continue;
+        }
+        // only the name and looks are left to chance
+        BuddyTemplate::random_for_round(&mut run_rng.shop, 1)
+            .with_stats(1, 1)
+            .with_color(*color)
+            .spawn(
+                &mut commands,
+                &assets,
+                index,
+                Side::Shop,
+                Transform::from_xyz(0.0, -500.0, 0.0),
+            );
+    }
+}
+
+/// Hands the shop to the other team, the way a versus game hands it to the other player
+fn switch_side(
+    mut players: ResMut<Players>,
+    mut workshop: ResMut<Workshop>,
+    mut buddy_drag_state: ResMut<BuddyDragState>,
+    mut interactions: Query<
+        (&Interaction, &mut UiColor),
+        (Changed<Interaction>, With<SideSwitchButton>),
+    >,
+) {
+    for (interaction, mut color) in interactions.iter_mut() {
+        match *interaction {
+            Interaction::Clicked => {
+                players.turn = players.turn.other();
+                // nothing of the last team's is left in hand or on the workbench
+                workshop.close();
+                *buddy_drag_state = BuddyDragState::None;
+            }
+            Interaction::Hovered => *color = HOVERED_BUTTON.into(),
+            Interaction::None => *color = NORMAL_BUTTON.into(),
+        }
+    }
+}
+
+fn set_side_switch_text(players: Res<Players>, mut texts: Query<&mut Text, With<SideSwitchText>>) {
+    let value = match players.turn.side() {
+        Side::Left => "Editing the left team",
+        _ => "Editing the right team",
+    };
+    // only touch the text when it changes, so it isn't laid out again every frame
+    for mut text in texts.iter_mut() {
+        if text.sections[0].value != value {
+            text.sections[0].value = value.to_string();
+        }
+    }
+}
+
+fn despawn_side_switch(mut commands: Commands, side_switch: Option<Res<SideSwitch>>) {
+    if let Some(side_switch) = side_switch {
+        commands.entity(side_switch.0).despawn_recursive();
+        commands.remove_resource::<SideSwitch>();
+    }
+}
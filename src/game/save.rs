@@ -0,0 +1,133 @@
+use crate::{
+    game::{
+        appearance::Appearance,
+        buddy::{Buddy, BuddyColor, BuddyFace, BuddyTemplate, Health, Side, Slot, Strength},
+        campaign::Campaign,
+        counters::{Coins, Trophies},
+        daily::DailyChallenge,
+        hot_seat::{Player, Players},
+        name::BuddyName,
+        online::Online,
+        persist,
+        puzzle::PuzzleRun,
+        workshop::Upgrades,
+    },
+    loading::GameAssets,
+};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// The state of the current run, written at the start of every shop phase so it can be continued
+/// from the main menu
+#[derive(Serialize, Deserialize)]
+pub struct RunSave {
+    pub coins: usize,
+    pub won: usize,
+    pub rounds: usize,
+    /// team buddies in slot order
+    pub team: Vec<BuddyTemplate>,
+}
+
+impl RunSave {
+    const SAVE_NAME: &'static str = "run";
+
+    pub fn exists() -> bool {
+        persist::exists(Self::SAVE_NAME)
+    }
+
+    pub fn load() -> Option<RunSave> {
+        persist::try_load(Self::SAVE_NAME)
+    }
+
+    pub fn delete() {
+        persist::delete(Self::SAVE_NAME);
+    }
+}
+
+/// Everything needed to turn the player's team back into templates
+pub type TeamQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static Side,
+        &'static Slot,
+        &'static BuddyName,
+        &'static BuddyFace,
+        &'static BuddyColor,
+        &'static Appearance,
+        &'static Upgrades,
+        &'static Health,
+        &'static Strength,
+    ),
+    With<Buddy>,
+>;
+
+/// The team on `side` in slot order
+pub fn team_templates(buddies: &TeamQuery, side: Side) -> Vec<BuddyTemplate> {
+    let mut team = buddies
+        .iter()
+        .filter(|(buddy_side, ..)| **buddy_side == side)
+        .map(
+            |(_, slot, name, face, color, appearance, upgrades, health, strength)| {
+                (
+                    slot.base,
+                    BuddyTemplate::from_buddy(
+                        name, face, color, appearance, upgrades, health, strength,
+                    ),
+                )
+            },
+        )
+        .collect::<Vec<_>>();
+    team.sort_by_key(|(slot, _)| *slot);
+    team.into_iter().map(|(_, template)| template).collect()
+}
+
+pub fn save_run(
+    coins: Res<Coins>,
+    trophies: Res<Trophies>,
+    players: Res<Players>,
+    online: Option<Res<Online>>,
+    daily: Option<Res<DailyChallenge>>,
+    puzzle: Option<Res<PuzzleRun>>,
+    campaign: Option<Res<Campaign>>,
+    buddies: TeamQuery,
+) {
+    // a versus game or the sandbox can't be continued on its own, and a daily challenge, a
+    // puzzle or a campaign is played in one go
+    if !players.keeps_score()
+        || online.is_some()
+        || daily.is_some()
+        || puzzle.is_some()
+        || campaign.is_some()
+    {
+        return;
+    }
+    persist::save(
+        RunSave::SAVE_NAME,
+        &RunSave {
+            coins: coins[Player::One],
+            won: trophies.won[Player::One],
+            rounds: trophies.rounds,
+            team: team_templates(&buddies, Side::Left),
+        },
+    );
+}
+
+/// Puts a saved run back into the world. The team is spawned on its pads and the shop takes it
+/// from there.
+pub fn restore_run(
+    commands: &mut Commands,
+    assets: &GameAssets,
+    run: &RunSave,
+    coins: &mut Coins,
+    trophies: &mut Trophies,
+) {
+    coins[Player::One] = run.coins;
+    trophies.won[Player::One] = run.won;
+    trophies.rounds = run.rounds;
+    for (slot, template) in run.team.iter().enumerate() {
+        template
+            .clone()
+            .spawn(commands, assets, slot, Side::Left, Transform::default());
+    }
+}
@@ -0,0 +1,572 @@
+//! Share codes pack a team into a short string like `BAB-0A1B2-C3D4E-...` that can be pasted
+//! anywhere. The code keeps every buddy's stats, face, color and upgrades in slot order, but not
+//! names or looks: those are hashed from the code itself, so a code always imports the same way.
+//! Buddies don't have levels; the upgrades bought for them in the workshop are how they grow, so
+//! those are what the code keeps.
+//!
+//! Layout, before the base32 text encoding: a version byte, the buddy count, five bytes per buddy
+//! and a Fletcher-16 checksum over everything before it.
+
+use crate::{
+    game::{
+        appearance::{splitmix, Appearance},
+        battle::Battle,
+        buddy::{Buddy, BuddyColor, BuddyFace, BuddyTemplate, Side, Slot},
+        daily::DailyChallenge,
+        hot_seat::Players,
+        name::BuddyName,
+        online::Online,
+        persist,
+        save::{team_templates, TeamQuery},
+        workshop::Upgrades,
+    },
+    loading::GameAssets,
+    menu::{HOVERED_BUTTON, NORMAL_BUTTON},
+    AppState,
+};
+use bevy::{prelude::*, ui::FocusPolicy};
+use std::fmt;
+
+/// Copies the team into a share code in the shop, and imports codes as the next opponent
+pub struct SharePlugin;
+
+impl Plugin for SharePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ShareState>()
+            .add_system_set(SystemSet::on_enter(AppState::Shop).with_system(spawn_share_bar))
+            .add_system_set(
+                SystemSet::on_update(AppState::Shop)
+                    .with_system(share_buttons)
+                    .with_system(type_share_code)
+                    .with_system(show_share_text),
+            )
+            .add_system_set(SystemSet::on_exit(AppState::Shop).with_system(despawn_share_bar));
+    }
+}
+
+const VERSION: u8 = 1;
+const PREFIX: &str = "BAB";
+/// Crockford's base32, which leaves out letters that are easy to mix up with digits
+const ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+/// characters between dashes, to make codes easier to read out
+const GROUP_LEN: usize = 5;
+const BYTES_PER_BUDDY: usize = 5;
+/// upgrade counts are packed into four bits each
+const MAX_UPGRADES: usize = 15;
+const EXPORT_FILE: &str = "team_code.txt";
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ShareCodeError {
+    NotACode,
+    UnknownVersion(u8),
+    BadChecksum,
+    TooManyBuddies,
+}
+
+impl fmt::Display for ShareCodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ShareCodeError::NotACode => write!(f, "That isn't a team code"),
+            ShareCodeError::UnknownVersion(version) => {
+                write!(
+                    f,
+                    "That code is from another version of the game ({})",
+                    version
+                )
+            }
+            ShareCodeError::BadChecksum => write!(f, "That code has a typo in it"),
+            ShareCodeError::TooManyBuddies => write!(f, "That team has too many buddies"),
+        }
+    }
+}
+
+pub fn encode(team: &[BuddyTemplate]) -> String {
+    let mut bytes = vec![VERSION, team.len() as u8];
+    for template in team {
+        let upgrades = template.upgrades();
+        let nibble = |count: usize| count.min(MAX_UPGRADES) as u8;
+        let face = match template.face() {
+            BuddyFace::Happy => 0,
+            BuddyFace::Neutral => 1,
+        };
+        let color = BuddyColor::COLORS
+            .iter()
+            .position(|color| *color == template.color())
+            .unwrap() as u8;
+        bytes.extend([
+            template.health().min(u8::MAX as usize) as u8,
+            template.strength().min(u8::MAX as usize) as u8,
+            face | color << 1,
+            nibble(upgrades.health) << 4 | nibble(upgrades.strength),
+            nibble(upgrades.face) << 4 | nibble(upgrades.color),
+        ]);
+    }
+    bytes.extend(checksum(&bytes).to_be_bytes());
+
+    let mut code = PREFIX.to_string();
+    for (i, character) in to_base32(&bytes).chars().enumerate() {
+        if i % GROUP_LEN == 0 {
+            code.push('-');
+        }
+        code.push(character);
+    }
+    code
+}
+
+pub fn decode(code: &str) -> Result<Vec<BuddyTemplate>, ShareCodeError> {
+    let code = code.trim().to_ascii_uppercase();
+    let body = code.strip_prefix(PREFIX).ok_or(ShareCodeError::NotACode)?;
+    let bytes = from_base32(body).ok_or(ShareCodeError::NotACode)?;
+    if bytes.len() < 4 {
+        return Err(ShareCodeError::NotACode);
+    }
+
+    let (data, sum) = bytes.split_at(bytes.len() - 2);
+    if checksum(data).to_be_bytes() != sum {
+        return Err(ShareCodeError::BadChecksum);
+    }
+    if data[0] != VERSION {
+        return Err(ShareCodeError::UnknownVersion(data[0]));
+    }
+    let count = data[1] as usize;
+    if count > Slot::MAX_PER_SIDE {
+        return Err(ShareCodeError::TooManyBuddies);
+    }
+    let buddies = &data[2..];
+    if buddies.len() != count * BYTES_PER_BUDDY {
+        return Err(ShareCodeError::NotACode);
+    }
+
+    // names and looks aren't in the code, so hash them from its checksum and the slot
+    let code_seed = u16::from_be_bytes([sum[0], sum[1]]) as u64;
+    let team = buddies
+        .chunks(BYTES_PER_BUDDY)
+        .enumerate()
+        .map(|(slot, buddy)| {
+            let seed = splitmix(code_seed, slot as u64);
+            let face = match buddy[2] & 1 {
+                0 => BuddyFace::Happy,
+                _ => BuddyFace::Neutral,
+            };
+            let color = BuddyColor::COLORS
+                .get((buddy[2] >> 1) as usize)
+                .copied()
+                .ok_or(ShareCodeError::NotACode)?;
+            let upgrades = Upgrades {
+                health: (buddy[3] >> 4) as usize,
+                strength: (buddy[3] & 0xf) as usize,
+                face: (buddy[4] >> 4) as usize,
+                color: (buddy[4] & 0xf) as usize,
+            };
+            Ok(BuddyTemplate::random_for_round(&mut rand::thread_rng(), 1)
+                // hashed again, so the name doesn't vary together with the looks
+                .with_name(&BuddyName::from_seed(splitmix(seed, 0)).0)
+                .with_appearance(Appearance { seed })
+                .with_stats(buddy[0] as usize, buddy[1] as usize)
+                .with_face(face)
+                .with_color(color)
+                .with_upgrades(upgrades))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(team)
+}
+
+fn checksum(bytes: &[u8]) -> u16 {
+    let (mut low, mut high) = (0u16, 0u16);
+    for byte in bytes {
+        low = (low + *byte as u16) % 255;
+        high = (high + low) % 255;
+    }
+    high << 8 | low
+}
+
+fn to_base32(bytes: &[u8]) -> String {
+    let mut text = String::new();
+    let (mut buffer, mut bits) = (0u32, 0);
+    for byte in bytes {
+        buffer = buffer << 8 | *byte as u32;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            text.push(ALPHABET[(buffer >> bits) as usize & 31] as char);
+        }
+    }
+    if bits > 0 {
+        text.push(ALPHABET[(buffer << (5 - bits)) as usize & 31] as char);
+    }
+    text
+}
+
+/// Reads base32 text back into bytes, skipping dashes and spaces. Letters that look like digits
+/// are read as those digits, like Crockford intended.
+fn from_base32(text: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::new();
+    let (mut buffer, mut bits) = (0u32, 0);
+    for character in text.chars() {
+        let character = match character {
+            '-' | ' ' => continue,
+            'O' => '0',
+            'I' | 'L' => '1',
+            _ => character,
+        };
+        let value = ALPHABET.iter().position(|c| *c as char == character)? as u32;
+        buffer = buffer << 5 | value;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+        }
+    }
+    Some(bytes)
+}
+
+#[derive(Default)]
+struct ShareState {
+    /// the code typed so far, while importing
+    importing: Option<String>,
+    message: String,
+}
+
+#[derive(Component, Copy, Clone)]
+enum ShareButton {
+    Share,
+    Import,
+}
+
+#[derive(Component)]
+struct ShareText;
+
+struct ShareBar(Entity);
+
+/// Importing replaces the next opponent, which only makes sense against the computer, and would
+/// make a daily challenge too easy
+fn can_import(players: &Players, online: Option<&Online>, daily: Option<&DailyChallenge>) -> bool {
+    !players.hot_seat && online.is_none() && daily.is_none()
+}
+
+fn spawn_share_bar(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    mut state: ResMut<ShareState>,
+    players: Res<Players>,
+    online: Option<Res<Online>>,
+    daily: Option<Res<DailyChallenge>>,
+) {
+    *state = ShareState::default();
+    let import = can_import(&players, online.as_deref(), daily.as_deref());
+    let font = assets.title_font.clone();
+    let bar = commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Auto),
+                position_type: PositionType::Absolute,
+                // ui space is y-up, so this sits along the bottom of the screen
+                position: Rect {
+                    top: Val::Px(10.0),
+                    ..Default::default()
+                },
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            color: Color::NONE.into(),
+            ..Default::default()
+        })
+        .insert(FocusPolicy::Pass)
+        .with_children(|parent| {
+            spawn_share_button(parent, &font, "Share Team", ShareButton::Share);
+            if import {
+                spawn_share_button(parent, &font, "Import", ShareButton::Import);
+            }
+            parent
+                .spawn_bundle(TextBundle {
+                    style: Style {
+                        margin: Rect {
+                            left: Val::Px(12.0),
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    },
+                    text: Text::with_section(
+                        "",
+                        TextStyle {
+                            font: font.clone(),
+                            font_size: 30.0,
+                            color: Color::hex("323232").unwrap(),
+                        },
+                        Default::default(),
+                    ),
+                    ..Default::default()
+                })
+                .insert(ShareText)
+                .insert(FocusPolicy::Pass);
+        })
+        .id();
+    commands.insert_resource(ShareBar(bar));
+}
+
+fn spawn_share_button(
+    parent: &mut ChildBuilder,
+    font: &Handle<Font>,
+    label: &str,
+    button: ShareButton,
+) {
+    parent
+        .spawn_bundle(ButtonBundle {
+            style: Style {
+                size: Size::new(Val::Px(130.0), Val::Px(40.0)),
+                margin: Rect::all(Val::Px(4.0)),
+                // horizontally center child text
+                justify_content: JustifyContent::Center,
+                // vertically center child text
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            color: NORMAL_BUTTON.into(),
+            ..Default::default()
+        })
+        .insert(button)
+        .with_children(|parent| {
+            parent.spawn_bundle(TextBundle {
+                text: Text::with_section(
+                    label,
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: 30.0,
+                        color: Color::rgb(0.9, 0.9, 0.9),
+                    },
+                    Default::default(),
+                ),
+                ..Default::default()
+            });
+        });
+}
+
+fn share_buttons(
+    mut state: ResMut<ShareState>,
+    players: Res<Players>,
+    team: TeamQuery,
+    mut interactions: Query<
+        (&Interaction, &ShareButton, &mut UiColor),
+        (Changed<Interaction>, With<Button>),
+    >,
+) {
+    for (interaction, button, mut color) in interactions.iter_mut() {
+        match *interaction {
+            Interaction::Clicked => match button {
+                ShareButton::Share => {
+                    let code = encode(&team_templates(&team, players.turn.side()));
+                    info!("team code: {}", code);
+                    // there's no clipboard to copy to, so the code goes in a file as well
+                    state.message = match persist::export_text(EXPORT_FILE, &code) {
+                        Some(path) => format!("{}\nSaved to {}", code, path.display()),
+                        None => code,
+                    };
+                    state.importing = None;
+                }
+                ShareButton::Import => state.importing = Some(String::new()),
+            },
+            Interaction::Hovered => *color = HOVERED_BUTTON.into(),
+            Interaction::None => *color = NORMAL_BUTTON.into(),
+        }
+    }
+}
+
+/// Reads a typed code, and brings its team in as the next opponent once Enter is pressed
+fn type_share_code(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    mut state: ResMut<ShareState>,
+    mut battle: ResMut<Battle>,
+    keys: Res<Input<KeyCode>>,
+    mut characters: EventReader<ReceivedCharacter>,
+    buddies: Query<(Entity, &Side), With<Buddy>>,
+) {
+    // always drain typed characters, so nothing typed before importing started leaks in
+    let typed = characters
+        .iter()
+        .map(|typed| typed.char)
+        .collect::<Vec<_>>();
+    let state = &mut *state;
+    let input = match &mut state.importing {
+        Some(input) => input,
+        None => return,
+    };
+
+    if keys.just_pressed(KeyCode::Back) {
+        input.pop();
+    }
+    for character in typed {
+        if (character.is_ascii_alphanumeric() || character == '-') && input.len() < 64 {
+            input.push(character.to_ascii_uppercase());
+        }
+    }
+
+    if keys.just_pressed(KeyCode::Escape) {
+        state.importing = None;
+        state.message.clear();
+    } else if keys.just_pressed(KeyCode::Return) {
+        state.message = match decode(input) {
+            Ok(team) => {
+                for (entity, side) in buddies.iter() {
+                    if *side == Side::Right {
+                        commands.entity(entity).despawn_recursive();
+                    }
+                }
+                battle.spawn_opponent(
+                    &mut commands,
+                    &assets,
+                    None,
+                    team.into_iter().map(|template| (template, None)).collect(),
+                    // off screen to the right, so a previewed team slides in
+                    Transform::from_xyz(900.0, 230.0, 0.0),
+                );
+                "Your next opponent is the imported team".to_string()
+            }
+            Err(err) => err.to_string(),
+        };
+        state.importing = None;
+    }
+}
+
+fn show_share_text(state: Res<ShareState>, mut texts: Query<&mut Text, With<ShareText>>) {
+    if !state.is_changed() {
+        return;
+    }
+    let value = match &state.importing {
+        Some(input) => format!("Type a code, then Enter: {}_", input),
+        None => state.message.clone(),
+    };
+    for mut text in texts.iter_mut() {
+        text.sections[0].value = value.clone();
+    }
+}
+
+fn despawn_share_bar(mut commands: Commands, bar: Res<ShareBar>) {
+    commands.entity(bar.0).despawn_recursive();
+    commands.remove_resource::<ShareBar>();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn buddy(
+        health: usize,
+        strength: usize,
+        face: BuddyFace,
+        color: BuddyColor,
+        upgrades: Upgrades,
+    ) -> BuddyTemplate {
+        BuddyTemplate::random_for_round(&mut StdRng::seed_from_u64(47), 1)
+            .with_stats(health, strength)
+            .with_face(face)
+            .with_color(color)
+            .with_upgrades(upgrades)
+    }
+
+    fn team() -> Vec<BuddyTemplate> {
+        vec![
+            buddy(
+                3,
+                2,
+                BuddyFace::Happy,
+                BuddyColor::Red,
+                Upgrades {
+                    health: 1,
+                    ..Default::default()
+                },
+            ),
+            buddy(
+                10,
+                7,
+                BuddyFace::Neutral,
+                BuddyColor::Green,
+                Upgrades {
+                    strength: 2,
+                    face: 1,
+                    ..Default::default()
+                },
+            ),
+            buddy(
+                1,
+                1,
+                BuddyFace::Happy,
+                BuddyColor::Blue,
+                Upgrades {
+                    color: 3,
+                    ..Default::default()
+                },
+            ),
+        ]
+    }
+
+    #[test]
+    fn codes_round_trip() {
+        let team = team();
+        let code = encode(&team);
+        assert!(code.starts_with("BAB-"), "{}", code);
+
+        let imported = decode(&code).unwrap();
+        assert_eq!(imported.len(), team.len());
+        for (imported, original) in imported.iter().zip(&team) {
+            assert_eq!(imported.health(), original.health());
+            assert_eq!(imported.strength(), original.strength());
+            assert_eq!(imported.color(), original.color());
+            assert_eq!(imported.upgrades(), original.upgrades());
+        }
+        // faces don't compare, but they're in the code like everything else
+        assert_eq!(encode(&imported), code);
+        assert_eq!(decode(&encode(&[])).unwrap().len(), 0);
+    }
+
+    #[test]
+    fn a_code_always_imports_the_same_names_and_looks() {
+        let code = encode(&team());
+        let first = ron::to_string(&decode(&code).unwrap()).unwrap();
+        let second = ron::to_string(&decode(&code).unwrap()).unwrap();
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn typos_fail_the_checksum() {
+        let code = encode(&team());
+        let typo = if &code[6..7] == "X" { "Y" } else { "X" };
+        let code = format!("{}{}{}", &code[..6], typo, &code[7..]);
+        assert_eq!(decode(&code).err(), Some(ShareCodeError::BadChecksum));
+    }
+
+    #[test]
+    fn codes_from_other_versions_are_turned_away() {
+        let mut bytes = vec![VERSION + 1, 0];
+        bytes.extend(checksum(&bytes).to_be_bytes());
+        let code = format!("{}-{}", PREFIX, to_base32(&bytes));
+        assert_eq!(
+            decode(&code).err(),
+            Some(ShareCodeError::UnknownVersion(VERSION + 1))
+        );
+    }
+
+    #[test]
+    fn anything_else_is_not_a_code() {
+        assert_eq!(decode("hello").err(), Some(ShareCodeError::NotACode));
+        assert_eq!(decode("BAB-U").err(), Some(ShareCodeError::NotACode));
+        assert_eq!(decode("").err(), Some(ShareCodeError::NotACode));
+    }
+
+    #[test]
+    fn letters_that_look_like_digits_read_as_those_digits() {
+        let code = encode(&team());
+        assert!(code.contains('0') && code.contains('1'), "{}", code);
+        // the prefix is left alone, and the last 1 is written as an I
+        let (prefix, body) = code.split_at(PREFIX.len());
+        let mut misread = body.replace('0', "O").replace('1', "L");
+        let last_one = misread.rfind('L').unwrap();
+        misread.replace_range(last_one..=last_one, "I");
+        let misread = format!("{}{}", prefix, misread).to_ascii_lowercase();
+
+        let imported = decode(&misread).unwrap();
+        assert_eq!(encode(&imported), code);
+    }
+}
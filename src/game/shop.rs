@@ -2,10 +2,19 @@ use crate::{
     game::{
         buddy::{Buddy, BuddyTemplate, Side, Slot},
         counters::{set_coin_text, set_trophies_text, Coins, Trophies},
+        events::{BuddyBought, BuddyMoved, BuddySold, ShopRefreshed},
+        hot_seat::Players,
+        online::Online,
         pad::{position_pad, spawn_pad},
+        puzzle::PuzzleRun,
+        rules::Rules,
+        run_rng::RunRng,
+        save::{save_run, team_templates, TeamQuery},
         ui::UiRoot,
+        workshop::{Workbench, Workshop},
         Z_BUDDY,
     },
+    loading::GameAssets,
     AppState,
 };
 use bevy::{
@@ -20,11 +29,15 @@ pub struct ShopPlugin;
 
 impl Plugin for ShopPlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(Coins(6))
-            .insert_resource(Trophies { won: 0, rounds: 0 })
+        app.insert_resource(Coins::new(STARTING_COINS))
+            .init_resource::<Trophies>()
             .insert_resource(BuddyDragState::None)
             .add_system_set(SystemSet::on_enter(AppState::Startup).with_system(spawn_shop_base))
-            .add_system_set(SystemSet::on_enter(AppState::Shop).with_system(enter_shop))
+            .add_system_set(
+                SystemSet::on_enter(AppState::Shop)
+                    .with_system(enter_shop)
+                    .with_system(save_run),
+            )
             .add_system_set(
                 SystemSet::on_update(AppState::Shop)
                     .with_system(set_coin_text)
@@ -38,7 +51,11 @@ impl Plugin for ShopPlugin {
     }
 }
 
-const SHOP_BUDDY_SLOTS: usize = 3;
+pub const SHOP_BUDDY_SLOTS: usize = 3;
+pub const STARTING_COINS: usize = 6;
+pub const BUDDY_PRICE: usize = 2;
+/// shop buddies are rolled this many rounds ahead of the battle they're bought for
+pub const SHOP_ROUNDS_AHEAD: usize = 2;
 
 #[derive(Component)]
 pub struct ShopPad;
@@ -46,9 +63,9 @@ pub struct ShopPad;
 #[derive(Component)]
 pub struct Trash;
 
-pub fn spawn_shop_base(mut commands: Commands, asset_server: Res<AssetServer>) {
-    for i in 0..SHOP_BUDDY_SLOTS {
-        spawn_pad(&mut commands, &asset_server, Side::Shop, Slot::new(i));
+pub fn spawn_shop_base(mut commands: Commands, assets: Res<GameAssets>, rules: Res<Rules>) {
+    for i in 0..rules.shop_slots() {
+        spawn_pad(&mut commands, &assets, Side::Shop, Slot::new(i));
     }
 }
 
@@ -59,16 +76,21 @@ pub struct ShopState {
 
 pub fn enter_shop(
     mut commands: Commands,
-    asset_server: Res<AssetServer>,
+    assets: Res<GameAssets>,
     trophies: Res<Trophies>,
+    players: Res<Players>,
+    rules: Res<Rules>,
+    mut run_rng: ResMut<RunRng>,
+    mut refreshed_events: EventWriter<ShopRefreshed>,
+    puzzle: Option<Res<PuzzleRun>>,
     ui_root: Query<Entity, With<UiRoot>>,
     buddies: Query<(Entity, &Side), With<Buddy>>,
 ) {
     let ui_root = ui_root.single();
-    let battle_button = spawn_battle_button(&mut commands, &asset_server, ui_root);
+    let battle_button = spawn_battle_button(&mut commands, &assets, ui_root);
     let trash = commands
         .spawn_bundle(SpriteBundle {
-            texture: asset_server.load("trash.png"),
+            texture: assets.trash.clone(),
             transform: Transform::from_xyz(-450.0, 40.0, Z_BUDDY - 1.0)
                 .with_scale(Vec3::new(0.8, 0.8, 1.0)),
             ..Default::default()
@@ -80,6 +102,37 @@ pub fn enter_shop(
         trash,
     });
 
+    // the sandbox keeps its own shop stocked
+    if players.sandbox {
+        return;
+    }
+    // puzzles offer the same buddies on every attempt
+    if let Some(puzzle) = puzzle {
+        puzzle.stock_shop(&mut commands, &assets, &buddies);
+        refreshed_events.send(ShopRefreshed);
+        return;
+    }
+    stock_shop(
+        &mut commands,
+        &assets,
+        &rules,
+        &mut run_rng,
+        trophies.rounds,
+        &mut refreshed_events,
+        &buddies,
+    );
+}
+
+/// Replaces whatever is left in the shop with fresh buddies
+fn stock_shop(
+    commands: &mut Commands,
+    assets: &GameAssets,
+    rules: &Rules,
+    run_rng: &mut RunRng,
+    rounds: usize,
+    refreshed_events: &mut EventWriter<ShopRefreshed>,
+    buddies: &Query<(Entity, &Side), With<Buddy>>,
+) {
     // clean up old shop entities
     for (entity, side) in buddies.iter() {
         if *side == Side::Shop {
@@ -87,34 +140,40 @@ pub fn enter_shop(
         }
     }
 
-    for i in 0..SHOP_BUDDY_SLOTS {
-        let template = BuddyTemplate::random_for_round(trophies.rounds + 2);
+    for i in 0..rules.shop_slots() {
+        let template =
+            BuddyTemplate::random_for_round(&mut run_rng.shop, rounds + SHOP_ROUNDS_AHEAD);
+        let template = rules.restyle(template);
         let buddy_id = template.spawn(
-            &mut commands,
-            &asset_server,
+            commands,
+            assets,
             i,
             Side::Shop,
             Transform::from_xyz(0.0, -500.0, 0.0),
         );
-        add_price(&mut commands, &asset_server, buddy_id, 2);
+        add_price(commands, assets, buddy_id, BUDDY_PRICE);
     }
+    refreshed_events.send(ShopRefreshed);
 }
 
 pub fn exit_shop(
     mut commands: Commands,
     shop_state: Res<ShopState>,
+    players: Res<Players>,
     mut buddies: Query<(Entity, &mut Slot, &Side), With<Buddy>>,
 ) {
-    let mut left_slots = Vec::new();
-    for (entity, slot, side) in buddies.iter_mut() {
-        if *side == Side::Left {
-            left_slots.push((entity, slot.base));
+    for player in players.all() {
+        let mut team_slots = Vec::new();
+        for (entity, slot, side) in buddies.iter_mut() {
+            if *side == player.side() {
+                team_slots.push((entity, slot.base));
+            }
+        }
+        team_slots.sort_by_key(|(_, slot)| *slot);
+        for (new_slot, (entity, _slot)) in team_slots.iter().enumerate() {
+            let mut slot = buddies.get_component_mut::<Slot>(*entity).unwrap();
+            *slot = Slot::new(new_slot);
         }
-    }
-    left_slots.sort_by_key(|(_, slot)| *slot);
-    for (new_slot, (entity, _slot)) in left_slots.iter().enumerate() {
-        let mut slot = buddies.get_component_mut::<Slot>(*entity).unwrap();
-        *slot = Slot::new(new_slot);
     }
     commands
         .entity(shop_state.battle_button)
@@ -125,11 +184,7 @@ pub fn exit_shop(
 #[derive(Component)]
 pub struct BattleButton;
 
-fn spawn_battle_button(
-    commands: &mut Commands,
-    asset_server: &AssetServer,
-    ui_root: Entity,
-) -> Entity {
+fn spawn_battle_button(commands: &mut Commands, assets: &GameAssets, ui_root: Entity) -> Entity {
     let mut battle_button = None;
     commands.entity(ui_root).with_children(|parent| {
         parent
@@ -170,9 +225,36 @@ fn spawn_battle_button(
                         .with_children(|parent| {
                             parent
                                 .spawn_bundle(ImageBundle {
-                                    image: asset_server.load("battle_button.png").into(),
+                                    image: assets.battle_button.clone().into(),
+                                    ..Default::default()
+                                })
+                                .insert(FocusPolicy::Pass);
+                            parent
+                                .spawn_bundle(TextBundle {
+                                    style: Style {
+                                        position_type: PositionType::Absolute,
+                                        // ui space is y-up, so this sits just under the button
+                                        position: Rect {
+                                            bottom: Val::Px(190.0),
+                                            ..Default::default()
+                                        },
+                                        ..Default::default()
+                                    },
+                                    text: Text::with_section(
+                                        "",
+                                        TextStyle {
+                                            font: assets.title_font.clone(),
+                                            font_size: 30.0,
+                                            color: Color::rgb(0.6, 0.15, 0.15),
+                                        },
+                                        TextAlignment {
+                                            vertical: VerticalAlign::Top,
+                                            horizontal: HorizontalAlign::Center,
+                                        },
+                                    ),
                                     ..Default::default()
                                 })
+                                .insert(EmptyTeamWarning)
                                 .insert(FocusPolicy::Pass);
                         })
                         .id(),
@@ -183,13 +265,81 @@ fn spawn_battle_button(
     battle_button.unwrap()
 }
 
+#[derive(Component)]
+pub struct EmptyTeamWarning;
+
 pub fn battle_button(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
     mut state: ResMut<State<AppState>>,
+    mut players: ResMut<Players>,
+    trophies: Res<Trophies>,
+    rules: Res<Rules>,
+    mut run_rng: ResMut<RunRng>,
+    mut workshop: ResMut<Workshop>,
+    mut buddy_drag_state: ResMut<BuddyDragState>,
+    mut refreshed_events: EventWriter<ShopRefreshed>,
+    mut online: Option<ResMut<Online>>,
     interaction_query: Query<&Interaction, (Changed<Interaction>, With<BattleButton>)>,
+    buddies: Query<(Entity, &Side), With<Buddy>>,
+    team: TeamQuery,
+    mut warnings: Query<&mut Text, With<EmptyTeamWarning>>,
 ) {
+    // the warning only stands while the team is empty, so emptying it again warns again
+    let team_side = players.turn.side();
+    if buddies.iter().any(|(_, side)| *side == team_side) {
+        for mut text in warnings.iter_mut() {
+            if !text.sections[0].value.is_empty() {
+                text.sections[0].value.clear();
+            }
+        }
+    }
+
     for interaction in interaction_query.iter() {
         if *interaction == Interaction::Clicked {
-            state.set(AppState::Battle).unwrap();
+            let team_side = players.turn.side();
+            let team_empty = !buddies.iter().any(|(_, side)| *side == team_side);
+            // an empty team can't win, so the first click only warns about it
+            let mut warned = true;
+            for mut text in warnings.iter_mut() {
+                if team_empty && text.sections[0].value.is_empty() {
+                    text.sections[0].value =
+                        "Your team is empty!\nClick again to battle".to_string();
+                    warned = false;
+                }
+            }
+            if !warned {
+                continue;
+            }
+
+            if let Some(online) = online.as_mut().filter(|online| online.playing()) {
+                // online battles start once the opponent has locked in too
+                online.lock_in(
+                    &mut commands,
+                    &mut workshop,
+                    team_templates(&team, team_side),
+                    &buddies,
+                );
+            } else if players.next_turn() {
+                // the next player gets a shop of their own, and nothing of the last player's
+                // left in their hands
+                workshop.close();
+                *buddy_drag_state = BuddyDragState::None;
+                for mut text in warnings.iter_mut() {
+                    text.sections[0].value.clear();
+                }
+                stock_shop(
+                    &mut commands,
+                    &assets,
+                    &rules,
+                    &mut run_rng,
+                    trophies.rounds,
+                    &mut refreshed_events,
+                    &buddies,
+                );
+            } else {
+                state.set(AppState::Battle).unwrap();
+            }
         }
     }
 }
@@ -204,34 +354,28 @@ pub enum BuddyDragState {
 fn buy_buddy(
     mut commands: Commands,
     mut coins: ResMut<Coins>,
+    players: Res<Players>,
     mut buddy_drag_state: ResMut<BuddyDragState>,
+    mut bought_events: EventWriter<BuddyBought>,
+    mut sold_events: EventWriter<BuddySold>,
+    mut moved_events: EventWriter<BuddyMoved>,
     mouse_button: Res<Input<MouseButton>>,
     windows: Res<Windows>,
     cameras: Query<(&Camera, &GlobalTransform)>,
     mut buddies: Query<(Entity, &Transform, &mut Slot, &mut Side, Option<&Price>), With<Buddy>>,
     trash: Query<&Transform, (With<Trash>, Without<Buddy>)>,
+    workbench: Query<&Transform, (With<Workbench>, Without<Buddy>)>,
+    mut workshop: ResMut<Workshop>,
     children: Query<&Children>,
-    price_counters: Query<&PriceCounter>,
-    price_icons: Query<&PriceIcon>,
+    price_parts: Query<Entity, Or<(With<PriceCounter>, With<PriceIcon>)>>,
 ) {
-    let window = windows.get_primary().unwrap();
-    let (camera, global_transform) = cameras
-        .iter()
-        .find(|(camera, _)| camera.name.as_deref() == Some(CameraPlugin::CAMERA_2D))
-        .unwrap();
-    let cursor_screen = if let Some(cursor) = window.cursor_position() {
+    let team_side = players.turn.side();
+    let cursor_world = if let Some(cursor) = cursor_world(&windows, &cameras) {
         cursor
     } else {
         return;
     };
 
-    let cursor_world = screen_to_world(
-        Vec2::new(window.width(), window.height()),
-        cursor_screen,
-        camera,
-        global_transform,
-    );
-
     if let BuddyDragState::Dragging { offset, .. } = &mut *buddy_drag_state {
         *offset = cursor_world;
     }
@@ -239,7 +383,7 @@ fn buy_buddy(
         let occupied_slots = buddies
             .iter()
             .filter_map(|(_, _, slot, side, _)| {
-                if *side == Side::Left {
+                if *side == team_side {
                     Some(slot.current)
                 } else {
                     None
@@ -249,30 +393,31 @@ fn buy_buddy(
         for (entity, transform, mut slot, mut side, price) in buddies.iter_mut() {
             if on_buddy(cursor_world, transform) {
                 match *side {
-                    Side::Left => {
-                        *buddy_drag_state = BuddyDragState::Dragging {
-                            buddy: entity,
-                            offset: cursor_world,
-                        }
-                    }
                     Side::Shop => {
-                        if coins.0 >= price.unwrap().0 {
+                        // the sandbox's buddies have no price on them
+                        let price = price.map_or(0, |price| price.0);
+                        if coins[players.turn] >= price {
                             let open_slot = (0..3).find(|i| !occupied_slots.contains(i));
                             if let Some(open_slot) = open_slot {
-                                *side = Side::Left;
+                                *side = team_side;
                                 *slot = Slot::new(open_slot);
-                                coins.0 -= price.unwrap().0;
-                                remove_price(
-                                    &mut commands,
-                                    entity,
-                                    &children,
-                                    &price_counters,
-                                    &price_icons,
-                                )
+                                coins[players.turn] -= price;
+                                bought_events.send(BuddyBought {
+                                    buddy: entity,
+                                    price,
+                                });
+                                remove_price(&mut commands, entity, &children, &price_parts)
                             }
                         }
                     }
-                    Side::Right => error!("how did this even happen"),
+                    buddy_side if buddy_side == team_side => {
+                        *buddy_drag_state = BuddyDragState::Dragging {
+                            buddy: entity,
+                            offset: cursor_world,
+                        }
+                    }
+                    // the opponent preview can be looked at, but not touched
+                    _ => {}
                 }
                 break;
             }
@@ -283,8 +428,13 @@ fn buy_buddy(
             let old_buddy_slot = buddies.get_component::<Slot>(*buddy).unwrap().current;
             let mut new_buddy_slot = None;
             for (current, transform, mut slot, side, _) in buddies.iter_mut() {
-                if on_buddy(cursor_world, transform) && current != *buddy && *side == Side::Left {
+                if on_buddy(cursor_world, transform) && current != *buddy && *side == team_side {
                     new_buddy_slot = Some(slot.base);
+                    moved_events.send(BuddyMoved {
+                        buddy: current,
+                        from: slot.base,
+                        to: old_buddy_slot,
+                    });
                     *slot = Slot::new(old_buddy_slot);
                     break;
                 }
@@ -293,15 +443,43 @@ fn buy_buddy(
             if let Some(new_buddy_slot) = new_buddy_slot {
                 let mut slot = buddies.get_component_mut::<Slot>(*buddy).unwrap();
                 *slot = Slot::new(new_buddy_slot);
+                moved_events.send(BuddyMoved {
+                    buddy: *buddy,
+                    from: old_buddy_slot,
+                    to: new_buddy_slot,
+                });
             } else if on_buddy(cursor_world, trash.single()) {
+                sold_events.send(BuddySold { buddy: *buddy });
                 commands.entity(*buddy).despawn_recursive();
+            } else if on_buddy(cursor_world, workbench.single()) {
+                workshop.open(*buddy);
             }
         }
         *buddy_drag_state = BuddyDragState::None;
     }
 }
 
-fn on_buddy(position: Vec2, buddy_transform: &Transform) -> bool {
+/// The cursor position in 2d world space, or `None` if the cursor isn't over the window
+pub fn cursor_world(
+    windows: &Windows,
+    cameras: &Query<(&Camera, &GlobalTransform)>,
+) -> Option<Vec2> {
+    let window = windows.get_primary().unwrap();
+    let (camera, global_transform) = cameras
+        .iter()
+        .find(|(camera, _)| camera.name.as_deref() == Some(CameraPlugin::CAMERA_2D))
+        .unwrap();
+    let cursor_screen = window.cursor_position()?;
+
+    Some(screen_to_world(
+        Vec2::new(window.width(), window.height()),
+        cursor_screen,
+        camera,
+        global_transform,
+    ))
+}
+
+pub fn on_buddy(position: Vec2, buddy_transform: &Transform) -> bool {
     let pos = buddy_transform.translation;
     let min = pos.xy() - BUDDY_EXTENTS;
     let max = pos.xy() + BUDDY_EXTENTS;
@@ -329,12 +507,7 @@ pub struct PriceCounter;
 #[derive(Component)]
 pub struct PriceIcon;
 
-pub fn add_price(
-    commands: &mut Commands,
-    asset_server: &AssetServer,
-    entity: Entity,
-    price: usize,
-) {
+pub fn add_price(commands: &mut Commands, assets: &GameAssets, entity: Entity, price: usize) {
     commands
         .entity(entity)
         .insert(Price(price))
@@ -343,7 +516,7 @@ pub fn add_price(
                 .spawn_bundle(SpriteBundle {
                     transform: Transform::from_xyz(-90.0, 70.0, Z_BUDDY + 0.3)
                         .with_scale(Vec3::splat(0.5)),
-                    texture: asset_server.load("price.png"),
+                    texture: assets.price.clone(),
                     ..Default::default()
                 })
                 .insert(PriceIcon);
@@ -353,7 +526,7 @@ pub fn add_price(
                     text: Text::with_section(
                         "0",
                         TextStyle {
-                            font: asset_server.load("font/CaveatBrush-Regular.ttf"),
+                            font: assets.number_font.clone(),
                             font_size: 70.0,
                             color: Color::hex("323232").unwrap(),
                         },
@@ -376,16 +549,12 @@ fn remove_price(
     commands: &mut Commands,
     entity: Entity,
     children: &Query<&Children>,
-    price_counters: &Query<&PriceCounter>,
-    price_icons: &Query<&PriceIcon>,
+    price_parts: &Query<Entity, Or<(With<PriceCounter>, With<PriceIcon>)>>,
 ) {
     // commands.entity(entity).remove::<Price>();
     if let Ok(children) = children.get(entity) {
         for child in children.iter().copied() {
-            if price_icons.get(child).is_ok() {
-                commands.entity(child).despawn();
-            }
-            if price_counters.get(child).is_ok() {
+            if price_parts.get(child).is_ok() {
                 commands.entity(child).despawn();
             }
         }
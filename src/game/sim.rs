@@ -0,0 +1,289 @@
+use crate::game::{
+    boss::{Boss, BossAbility},
+    buddy::{Attribute, BuddyColor, BuddyTemplate},
+    events::BattleResult,
+    rules::Rules,
+    synergy::{Shield, Synergy},
+};
+
+/// Extra damage for hitting a color this buddy's color beats, when that rule is on
+pub const ADVANTAGE_BONUS: usize = 1;
+
+/// the damage an attack deals, and whether it was effective
+pub fn attack_damage(
+    rules: &Rules,
+    attacker: BuddyColor,
+    target: BuddyColor,
+    strength: usize,
+) -> (usize, bool) {
+    if rules.color_advantage && attacker.beats(&target) {
+        (strength + ADVANTAGE_BONUS, true)
+    } else {
+        (strength, false)
+    }
+}
+
+/// the damage an attacker takes back for hitting a buddy with this ability
+pub fn thorns_damage(target: Option<BossAbility>) -> usize {
+    match target {
+        Some(BossAbility::Thorns) => BossAbility::THORNS_DAMAGE,
+        _ => 0,
+    }
+}
+
+/// buddies that regenerate heal after every exchange they survive, up to their base health
+pub fn regeneration(ability: Option<BossAbility>, health: usize, base: usize) -> usize {
+    match ability {
+        Some(BossAbility::Regenerate) if health > 0 && health < base => {
+            BossAbility::REGENERATE_HEALTH.min(base - health)
+        }
+        _ => 0,
+    }
+}
+
+/// coins paid out for a battle, including the bonus for beating a boss
+pub fn reward(rules: &Rules, result: BattleResult, boss: Option<Boss>) -> usize {
+    let boss_bonus = match boss {
+        Some(boss) if result == BattleResult::Win => boss.bonus_reward(),
+        _ => 0,
+    };
+    let reward = result.reward() + boss_bonus;
+    if rules.double_rewards {
+        reward * 2
+    } else {
+        reward
+    }
+}
+
+/// Battles stop here and count as a tie, in case two buddies heal as fast as they're hurt
+const MAX_EXCHANGES: usize = 1000;
+
+/// How a battle fought without any animation turned out
+#[derive(Debug, Copy, Clone)]
+pub struct Outcome {
+    pub result: BattleResult,
+    /// how many times the front buddies hit each other
+    pub exchanges: usize,
+}
+
+struct Fighter {
+    health: Attribute,
+    strength: Attribute,
+    color: BuddyColor,
+    ability: Option<BossAbility>,
+    shield: Shield,
+}
+
+fn fighters(team: &[(BuddyTemplate, Option<BossAbility>)]) -> Vec<Fighter> {
+    let mut fighters = team
+        .iter()
+        .map(|(template, ability)| Fighter {
+            health: Attribute::new(template.health()),
+            strength: Attribute::new(template.strength()),
+            color: template.color(),
+            ability: *ability,
+            shield: Shield(0),
+        })
+        .collect::<Vec<_>>();
+    for synergy in Synergy::for_team(fighters.iter().map(|fighter| fighter.color)) {
+        for fighter in fighters.iter_mut() {
+            if fighter.color != synergy.color {
+                continue;
+            }
+            match synergy.color {
+                BuddyColor::Red => fighter.strength.add(synergy.tier),
+                BuddyColor::Green => fighter.health.add(synergy.tier),
+                BuddyColor::Blue => fighter.shield = Shield(synergy.shield()),
+            }
+        }
+    }
+    fighters
+}
+
+/// Fights a battle with the same rules as the battle screen, front buddies first. Teams are in
+/// slot order.
+pub fn fight(
+    rules: &Rules,
+    left: &[(BuddyTemplate, Option<BossAbility>)],
+    right: &[(BuddyTemplate, Option<BossAbility>)],
+) -> Outcome {
+    let mut left = fighters(left);
+    let mut right = fighters(right);
+    let mut exchanges = 0;
+    while !left.is_empty() && !right.is_empty() && exchanges < MAX_EXCHANGES {
+        exchanges += 1;
+        let (left_front, right_front) = (&mut left[0], &mut right[0]);
+        let (left_attack, _) = attack_damage(
+            rules,
+            left_front.color,
+            right_front.color,
+            left_front.strength.value(),
+        );
+        let (right_attack, _) = attack_damage(
+            rules,
+            right_front.color,
+            left_front.color,
+            right_front.strength.value(),
+        );
+        let left_damage = left_front
+            .shield
+            .absorb(right_attack + thorns_damage(right_front.ability));
+        let right_damage = right_front
+            .shield
+            .absorb(left_attack + thorns_damage(left_front.ability));
+        for (fighter, damage) in [(left_front, left_damage), (right_front, right_damage)] {
+            fighter.health.remove(damage);
+            let amount = regeneration(
+                fighter.ability,
+                fighter.health.value(),
+                fighter.health.base(),
+            );
+            fighter.health.add(amount);
+        }
+        if left[0].health.value() == 0 {
+            left.remove(0);
+        }
+        if right[0].health.value() == 0 {
+            right.remove(0);
+        }
+    }
+
+    let result = match (left.is_empty(), right.is_empty()) {
+        (false, true) => BattleResult::Win,
+        (true, false) => BattleResult::Lose,
+        _ => BattleResult::Tie,
+    };
+    Outcome { result, exchanges }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+    use BuddyColor::*;
+
+    fn buddy(
+        health: usize,
+        strength: usize,
+        color: BuddyColor,
+    ) -> (BuddyTemplate, Option<BossAbility>) {
+        let template = BuddyTemplate::random_for_round(&mut StdRng::seed_from_u64(0), 1)
+            .with_stats(health, strength)
+            .with_color(color);
+        (template, None)
+    }
+
+    fn with_ability(
+        (template, _): (BuddyTemplate, Option<BossAbility>),
+        ability: BossAbility,
+    ) -> (BuddyTemplate, Option<BossAbility>) {
+        (template, Some(ability))
+    }
+
+    fn fight_result(
+        rules: &Rules,
+        left: &[(BuddyTemplate, Option<BossAbility>)],
+        right: &[(BuddyTemplate, Option<BossAbility>)],
+    ) -> (BattleResult, usize) {
+        let outcome = fight(rules, left, right);
+        (outcome.result, outcome.exchanges)
+    }
+
+    #[test]
+    fn an_empty_team_loses_without_a_fight() {
+        let rules = Rules::default();
+        let team = [buddy(1, 1, Red)];
+        assert_eq!(fight_result(&rules, &[], &[]), (BattleResult::Tie, 0));
+        assert_eq!(fight_result(&rules, &team, &[]), (BattleResult::Win, 0));
+        assert_eq!(fight_result(&rules, &[], &team), (BattleResult::Lose, 0));
+    }
+
+    #[test]
+    fn the_next_buddy_steps_up_when_the_front_one_falls() {
+        let rules = Rules::default();
+        let left = [buddy(3, 2, Red)];
+        let right = [buddy(2, 1, Green), buddy(1, 1, Blue)];
+        assert_eq!(fight_result(&rules, &left, &right), (BattleResult::Win, 2));
+        assert_eq!(fight_result(&rules, &right, &left), (BattleResult::Lose, 2));
+    }
+
+    #[test]
+    fn color_advantage_adds_damage_only_when_the_rule_is_on() {
+        let left = [buddy(2, 1, Red)];
+        let right = [buddy(2, 1, Green)];
+        assert_eq!(
+            fight_result(&Rules::default(), &left, &right),
+            (BattleResult::Tie, 2)
+        );
+        let rules = Rules {
+            color_advantage: true,
+            ..Default::default()
+        };
+        assert_eq!(fight_result(&rules, &left, &right), (BattleResult::Win, 1));
+        assert_eq!(attack_damage(&rules, Red, Green, 3), (4, true));
+        assert_eq!(attack_damage(&rules, Green, Red, 3), (3, false));
+    }
+
+    #[test]
+    fn green_synergy_outlasts_a_tough_buddy() {
+        let rules = Rules::default();
+        let greens = [buddy(1, 1, Green), buddy(1, 1, Green)];
+        let mixed = [buddy(1, 1, Green), buddy(1, 1, Blue)];
+        let tough = [buddy(3, 1, Red)];
+        assert_eq!(
+            fight_result(&rules, &greens, &tough),
+            (BattleResult::Win, 3)
+        );
+        assert_eq!(
+            fight_result(&rules, &mixed, &tough),
+            (BattleResult::Lose, 2)
+        );
+    }
+
+    #[test]
+    fn blue_shields_soak_the_first_hits() {
+        let rules = Rules::default();
+        let blues = [buddy(1, 1, Blue), buddy(1, 1, Blue)];
+        let tough = [buddy(3, 1, Red)];
+        assert_eq!(fight_result(&rules, &blues, &tough), (BattleResult::Win, 3));
+    }
+
+    #[test]
+    fn thorns_hurt_whoever_hits_them() {
+        let rules = Rules::default();
+        let left = [buddy(3, 1, Red)];
+        let plain = [buddy(2, 1, Blue)];
+        let thorny = [with_ability(buddy(2, 1, Blue), BossAbility::Thorns)];
+        assert_eq!(fight_result(&rules, &left, &plain), (BattleResult::Win, 2));
+        assert_eq!(fight_result(&rules, &left, &thorny), (BattleResult::Tie, 2));
+    }
+
+    #[test]
+    fn regeneration_stalemates_end_in_a_tie() {
+        let rules = Rules::default();
+        let left = [with_ability(buddy(3, 1, Green), BossAbility::Regenerate)];
+        let right = [with_ability(buddy(3, 1, Blue), BossAbility::Regenerate)];
+        assert_eq!(
+            fight_result(&rules, &left, &right),
+            (BattleResult::Tie, MAX_EXCHANGES)
+        );
+        // but a buddy at zero health stays down
+        assert_eq!(regeneration(Some(BossAbility::Regenerate), 0, 3), 0);
+        assert_eq!(regeneration(Some(BossAbility::Regenerate), 3, 3), 0);
+        assert_eq!(regeneration(Some(BossAbility::Regenerate), 2, 3), 1);
+    }
+
+    #[test]
+    fn rewards_add_the_boss_bonus_on_a_win() {
+        let rules = Rules::default();
+        assert_eq!(reward(&rules, BattleResult::Win, None), 5);
+        assert_eq!(reward(&rules, BattleResult::Win, Some(Boss::Giant)), 11);
+        assert_eq!(reward(&rules, BattleResult::Lose, Some(Boss::Giant)), 2);
+        let rules = Rules {
+            double_rewards: true,
+            ..Default::default()
+        };
+        assert_eq!(reward(&rules, BattleResult::Tie, None), 6);
+        assert_eq!(reward(&rules, BattleResult::Win, Some(Boss::BlueCrew)), 20);
+    }
+}
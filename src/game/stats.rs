@@ -0,0 +1,154 @@
+use crate::game::{
+    achievements::AchievementProgress,
+    buddy::Side,
+    campaign::Campaign,
+    counters::Trophies,
+    events::{BattleEnded, BattleResult, BuddyBought, BuddyFainted, BuddySold},
+    hot_seat::{Player, Players},
+    persist,
+    puzzle::PuzzleRun,
+    replay::ReplayPlayback,
+};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+pub struct StatsPlugin;
+
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Stats::load()).add_system(track_stats);
+    }
+}
+
+/// Lifetime statistics across every run, shown on the stats screen
+#[derive(Default, Serialize, Deserialize)]
+pub struct Stats {
+    pub battles: usize,
+    pub wins: usize,
+    pub losses: usize,
+    pub ties: usize,
+    pub buddies_bought: usize,
+    pub buddies_trashed: usize,
+    pub foes_defeated: usize,
+    pub coins_earned: usize,
+    pub most_trophies: usize,
+}
+
+impl Stats {
+    const SAVE_NAME: &'static str = "stats";
+
+    fn load() -> Stats {
+        let mut stats = persist::load::<Stats>(Self::SAVE_NAME);
+        let legacy = persist::try_load::<LegacyCounters>(AchievementProgress::SAVE_NAME);
+        if legacy.is_some_and(|legacy| stats.migrate(legacy)) {
+            persist::save(Self::SAVE_NAME, &stats);
+            // rewriting the achievements drops the old counters, so they're only carried over once
+            let progress = persist::load::<AchievementProgress>(AchievementProgress::SAVE_NAME);
+            persist::save(AchievementProgress::SAVE_NAME, &progress);
+        }
+        stats
+    }
+
+    /// Adds the counters an old achievements save kept, returns whether there were any
+    fn migrate(&mut self, legacy: LegacyCounters) -> bool {
+        self.buddies_bought += legacy.buddies_bought;
+        self.buddies_trashed += legacy.buddies_trashed;
+        legacy.buddies_bought > 0 || legacy.buddies_trashed > 0
+    }
+}
+
+/// Counters the achievements save used to keep before they moved to [`Stats`]
+#[derive(Deserialize)]
+struct LegacyCounters {
+    #[serde(default)]
+    buddies_bought: usize,
+    #[serde(default)]
+    buddies_trashed: usize,
+}
+
+fn track_stats(
+    mut stats: ResMut<Stats>,
+    trophies: Res<Trophies>,
+    mut bought_events: EventReader<BuddyBought>,
+    mut sold_events: EventReader<BuddySold>,
+    mut fainted_events: EventReader<BuddyFainted>,
+    mut battle_ended_events: EventReader<BattleEnded>,
+    playback: Option<Res<ReplayPlayback>>,
+    puzzle: Option<Res<PuzzleRun>>,
+    campaign: Option<Res<Campaign>>,
+    players: Res<Players>,
+) {
+    // watching a replay isn't playing, puzzles and the campaign are practice, and versus games
+    // and the sandbox don't count towards the single player stats
+    if playback.is_some() || puzzle.is_some() || campaign.is_some() || !players.keeps_score() {
+        return;
+    }
+
+    // only deref mutably when something happened, so change detection stays meaningful
+    let bought = bought_events.iter().count();
+    let trashed = sold_events.iter().count();
+    let defeated = fainted_events
+        .iter()
+        .filter(|fainted| fainted.side == Side::Right)
+        .count();
+    let results = battle_ended_events
+        .iter()
+        .map(|ended| (ended.result, ended.reward))
+        .collect::<Vec<_>>();
+    let new_best = trophies.won[Player::One] > stats.most_trophies;
+
+    if bought == 0 && trashed == 0 && defeated == 0 && results.is_empty() && !new_best {
+        return;
+    }
+
+    stats.buddies_bought += bought;
+    stats.buddies_trashed += trashed;
+    stats.foes_defeated += defeated;
+    for (result, reward) in results {
+        stats.battles += 1;
+        stats.coins_earned += reward;
+        match result {
+            BattleResult::Win => stats.wins += 1,
+            BattleResult::Lose => stats.losses += 1,
+            BattleResult::Tie => stats.ties += 1,
+        }
+    }
+    if new_best {
+        stats.most_trophies = trophies.won[Player::One];
+    }
+    persist::save(Stats::SAVE_NAME, &*stats);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::achievements::Achievement;
+
+    #[test]
+    fn old_achievement_saves_carry_their_counters_over() {
+        let legacy: LegacyCounters = ron::from_str(
+            "(unlocked: [LoneWolf, BigSpender], buddies_bought: 31, buddies_trashed: 4)",
+        )
+        .unwrap();
+        let mut stats = Stats {
+            buddies_bought: 2,
+            ..Default::default()
+        };
+        assert!(stats.migrate(legacy));
+        assert_eq!(stats.buddies_bought, 33);
+        assert_eq!(stats.buddies_trashed, 4);
+    }
+
+    #[test]
+    fn current_achievement_saves_have_nothing_to_migrate() {
+        let progress = AchievementProgress {
+            unlocked: vec![Achievement::Flawless],
+        };
+        let saved = ron::ser::to_string_pretty(&progress, Default::default()).unwrap();
+        let legacy: LegacyCounters = ron::from_str(&saved).unwrap();
+        let mut stats = Stats::default();
+        assert!(!stats.migrate(legacy));
+        assert_eq!(stats.buddies_bought, 0);
+        assert_eq!(stats.buddies_trashed, 0);
+    }
+}
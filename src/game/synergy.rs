@@ -0,0 +1,301 @@
+use crate::{
+    game::{
+        battle::Battle,
+        buddy::{Buddy, BuddyColor, Health, Offset, Side, Strength},
+        hot_seat::Players,
+        ui::UiRoot,
+    },
+    loading::GameAssets,
+    AppState,
+};
+use bevy::prelude::*;
+use std::f32::consts::PI;
+
+/// Buddies of the same color on a team boost each other at the start of every battle
+pub struct SynergyPlugin;
+
+impl Plugin for SynergyPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SynergyBonus>()
+            .add_system_set(SystemSet::on_enter(AppState::Shop).with_system(spawn_synergy_text))
+            .add_system_set(SystemSet::on_update(AppState::Shop).with_system(set_synergy_text))
+            .add_system_set(SystemSet::on_exit(AppState::Shop).with_system(despawn_synergy_text))
+            .add_system_set(SystemSet::on_enter(AppState::Battle).with_system(reset_synergies))
+            .add_system_set(SystemSet::on_update(AppState::Battle).with_system(apply_synergies))
+            .add_system_set(SystemSet::on_exit(AppState::Battle).with_system(remove_shields));
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Synergy {
+    pub color: BuddyColor,
+    /// 1 for two buddies of a color, 2 for three
+    pub tier: usize,
+}
+
+impl Synergy {
+    const MIN_BUDDIES: usize = 2;
+
+    pub fn for_team(colors: impl Iterator<Item = BuddyColor>) -> Vec<Synergy> {
+        let colors = colors.collect::<Vec<_>>();
+        BuddyColor::COLORS
+            .iter()
+            .filter_map(|color| {
+                let count = colors.iter().filter(|c| *c == color).count();
+                if count >= Self::MIN_BUDDIES {
+                    Some(Synergy {
+                        color: *color,
+                        tier: count + 1 - Self::MIN_BUDDIES,
+                    })
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    pub fn description(&self) -> String {
+        let count = self.tier + Self::MIN_BUDDIES - 1;
+        match self.color {
+            BuddyColor::Red => format!("{} Red: +{} Strength", count, self.tier),
+            BuddyColor::Green => format!("{} Green: +{} Health", count, self.tier),
+            BuddyColor::Blue => format!("{} Blue: {} Shield", count, self.shield()),
+        }
+    }
+
+    pub fn shield(&self) -> usize {
+        self.tier * 2
+    }
+}
+
+/// Soaks up damage before it reaches a buddy's health. Only lasts for one battle.
+#[derive(Component)]
+pub struct Shield(pub usize);
+
+impl Shield {
+    /// returns the damage that got through
+    pub fn absorb(&mut self, damage: usize) -> usize {
+        let blocked = self.0.min(damage);
+        self.0 -= blocked;
+        damage - blocked
+    }
+}
+
+/// Which buddies got a bonus this battle, so they can be animated
+#[derive(Default)]
+struct SynergyBonus {
+    applied: bool,
+    boosted: Vec<Entity>,
+}
+
+fn reset_synergies(mut bonus: ResMut<SynergyBonus>) {
+    *bonus = SynergyBonus::default();
+}
+
+fn apply_synergies(
+    mut commands: Commands,
+    battle: Res<Battle>,
+    mut bonus: ResMut<SynergyBonus>,
+    mut buddies: Query<
+        (
+            Entity,
+            &Side,
+            &BuddyColor,
+            &mut Health,
+            &mut Strength,
+            &mut Offset,
+        ),
+        With<Buddy>,
+    >,
+) {
+    let percent = match battle.beginning() {
+        Some(percent) => percent,
+        None => {
+            for entity in bonus.boosted.drain(..) {
+                if let Ok((.., mut offset)) = buddies.get_mut(entity) {
+                    offset.0.scale = Vec3::ONE;
+                }
+            }
+            return;
+        }
+    };
+
+    // the enemy team is spawned when the battle starts, so this can't happen in on_enter
+    if !bonus.applied {
+        bonus.applied = true;
+        for side in [Side::Left, Side::Right] {
+            let synergies = Synergy::for_team(
+                buddies
+                    .iter()
+                    .filter(|(_, buddy_side, ..)| **buddy_side == side)
+                    .map(|(_, _, color, ..)| *color),
+            );
+            for synergy in synergies {
+                for (entity, buddy_side, color, mut health, mut strength, _) in buddies.iter_mut() {
+                    if *buddy_side != side || *color != synergy.color {
+                        continue;
+                    }
+                    match synergy.color {
+                        BuddyColor::Red => strength.0.add(synergy.tier),
+                        BuddyColor::Green => health.0.add(synergy.tier),
+                        BuddyColor::Blue => {
+                            commands.entity(entity).insert(Shield(synergy.shield()));
+                        }
+                    }
+                    bonus.boosted.push(entity);
+                }
+            }
+        }
+    }
+
+    // boosted buddies puff up while the battle begins
+    let scale = 1.0 + 0.25 * (percent * PI).sin();
+    for entity in bonus.boosted.iter() {
+        if let Ok((.., mut offset)) = buddies.get_mut(*entity) {
+            offset.0.scale = Vec3::new(scale, scale, 1.0);
+        }
+    }
+}
+
+fn remove_shields(mut commands: Commands, shields: Query<Entity, With<Shield>>) {
+    for entity in shields.iter() {
+        commands.entity(entity).remove::<Shield>();
+    }
+}
+
+#[derive(Component)]
+struct SynergyText;
+
+fn spawn_synergy_text(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    ui_root: Query<Entity, With<UiRoot>>,
+) {
+    commands.entity(ui_root.single()).with_children(|parent| {
+        parent
+            .spawn_bundle(TextBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    // ui space is y-up, so this sits at the top of the screen
+                    position: Rect {
+                        bottom: Val::Px(20.0),
+                        left: Val::Percent(40.0),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                text: Text::with_section(
+                    "",
+                    TextStyle {
+                        font: assets.title_font.clone(),
+                        font_size: 36.0,
+                        color: Color::hex("323232").unwrap(),
+                    },
+                    Default::default(),
+                ),
+                ..Default::default()
+            })
+            .insert(SynergyText);
+    });
+}
+
+fn set_synergy_text(
+    players: Res<Players>,
+    buddies: Query<(&Side, &BuddyColor), With<Buddy>>,
+    mut texts: Query<&mut Text, With<SynergyText>>,
+) {
+    let synergies = Synergy::for_team(
+        buddies
+            .iter()
+            .filter(|(side, _)| **side == players.turn.side())
+            .map(|(_, color)| *color),
+    );
+    let value = synergies
+        .iter()
+        .map(|synergy| synergy.description())
+        .collect::<Vec<_>>()
+        .join("   ");
+    for mut text in texts.iter_mut() {
+        if text.sections[0].value != value {
+            text.sections[0].value = value.clone();
+        }
+    }
+}
+
+fn despawn_synergy_text(mut commands: Commands, texts: Query<Entity, With<SynergyText>>) {
+    for entity in texts.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use BuddyColor::*;
+
+    #[test]
+    fn a_lone_buddy_has_no_synergy() {
+        assert!(Synergy::for_team([Red, Green, Blue].into_iter()).is_empty());
+        assert!(Synergy::for_team(std::iter::empty()).is_empty());
+    }
+
+    #[test]
+    fn each_extra_buddy_of_a_color_raises_the_tier() {
+        assert_eq!(
+            Synergy::for_team([Red, Red].into_iter()),
+            vec![Synergy {
+                color: Red,
+                tier: 1
+            }]
+        );
+        assert_eq!(
+            Synergy::for_team([Red, Red, Red].into_iter()),
+            vec![Synergy {
+                color: Red,
+                tier: 2
+            }]
+        );
+    }
+
+    #[test]
+    fn mixed_teams_list_every_color_in_order() {
+        let synergies = Synergy::for_team([Blue, Green, Blue, Red, Green, Green].into_iter());
+        assert_eq!(
+            synergies,
+            vec![
+                Synergy {
+                    color: Green,
+                    tier: 2
+                },
+                Synergy {
+                    color: Blue,
+                    tier: 1
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn descriptions_count_the_buddies() {
+        let green = Synergy {
+            color: Green,
+            tier: 2,
+        };
+        assert_eq!(green.description(), "3 Green: +2 Health");
+        let blue = Synergy {
+            color: Blue,
+            tier: 1,
+        };
+        assert_eq!(blue.shield(), 2);
+        assert_eq!(blue.description(), "2 Blue: 2 Shield");
+    }
+
+    #[test]
+    fn shields_only_soak_what_they_have() {
+        let mut shield = Shield(3);
+        assert_eq!(shield.absorb(2), 0);
+        assert_eq!(shield.absorb(2), 1);
+        assert_eq!(shield.0, 0);
+        assert_eq!(shield.absorb(4), 4);
+    }
+}
@@ -1,10 +1,13 @@
-use crate::game::counters::{spawn_coins_element, spawn_trophies_element};
+use crate::{
+    game::counters::{spawn_coins_element, spawn_trophies_element},
+    loading::GameAssets,
+};
 use bevy::prelude::*;
 
 #[derive(Component)]
 pub struct UiRoot;
 
-pub fn spawn_ui(commands: &mut Commands, asset_server: &AssetServer) {
+pub fn spawn_ui(commands: &mut Commands, assets: &GameAssets) {
     commands
         .spawn_bundle(NodeBundle {
             style: Style {
@@ -19,7 +22,7 @@ pub fn spawn_ui(commands: &mut Commands, asset_server: &AssetServer) {
         })
         .insert(UiRoot)
         .with_children(|parent| {
-            spawn_coins_element(parent, asset_server);
-            spawn_trophies_element(parent, asset_server);
+            spawn_coins_element(parent, assets);
+            spawn_trophies_element(parent, assets);
         });
 }
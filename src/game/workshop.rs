@@ -0,0 +1,437 @@
+use crate::{
+    game::{
+        buddy::{Buddy, BuddyColor, BuddyFace, Health, Strength},
+        counters::Coins,
+        events::{BuddySold, BuddyUpgraded},
+        hot_seat::Players,
+        name::BuddyName,
+        online::Online,
+        ui::UiRoot,
+        Z_BUDDY,
+    },
+    loading::GameAssets,
+    menu::{HOVERED_BUTTON, NORMAL_BUTTON},
+    AppState,
+};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Lets the player drop a team buddy on the workbench and spend coins making it better. In the
+/// sandbox everything is free, and stats can be taken away again.
+pub struct WorkshopPlugin;
+
+impl Plugin for WorkshopPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Workshop>()
+            .add_system_set(SystemSet::on_enter(AppState::Shop).with_system(spawn_workbench))
+            .add_system_set(
+                SystemSet::on_update(AppState::Shop)
+                    .with_system(workshop_buttons)
+                    .with_system(close_for_sold_buddy)
+                    .with_system(show_workshop_panel),
+            )
+            .add_system_set(SystemSet::on_exit(AppState::Shop).with_system(exit_workshop));
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Upgrade {
+    Health,
+    Strength,
+    Face,
+    Recolor,
+}
+
+impl Upgrade {
+    const ALL: &'static [Upgrade] = &[
+        Upgrade::Health,
+        Upgrade::Strength,
+        Upgrade::Face,
+        Upgrade::Recolor,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Upgrade::Health => "+1 Health",
+            Upgrade::Strength => "+1 Strength",
+            Upgrade::Face => "New Face",
+            Upgrade::Recolor => "Recolor",
+        }
+    }
+
+    /// the sandbox's button for undoing this upgrade, if it can be undone
+    fn lower_label(&self) -> Option<&'static str> {
+        match self {
+            Upgrade::Health => Some("-1 Health"),
+            Upgrade::Strength => Some("-1 Strength"),
+            // changing the face or color again already goes back around
+            Upgrade::Face | Upgrade::Recolor => None,
+        }
+    }
+
+    /// (price of the first purchase, how much each purchase adds to the next one)
+    fn pricing(&self) -> (usize, usize) {
+        match self {
+            Upgrade::Health | Upgrade::Strength => (3, 2),
+            Upgrade::Face | Upgrade::Recolor => (1, 1),
+        }
+    }
+}
+
+/// How many of each upgrade a buddy has had, which makes the next one pricier
+#[derive(Component, Debug, Default, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Upgrades {
+    pub health: usize,
+    pub strength: usize,
+    pub face: usize,
+    pub color: usize,
+}
+
+impl Upgrades {
+    fn count_mut(&mut self, upgrade: Upgrade) -> &mut usize {
+        match upgrade {
+            Upgrade::Health => &mut self.health,
+            Upgrade::Strength => &mut self.strength,
+            Upgrade::Face => &mut self.face,
+            Upgrade::Recolor => &mut self.color,
+        }
+    }
+
+    pub fn count(&self, upgrade: Upgrade) -> usize {
+        match upgrade {
+            Upgrade::Health => self.health,
+            Upgrade::Strength => self.strength,
+            Upgrade::Face => self.face,
+            Upgrade::Recolor => self.color,
+        }
+    }
+
+    pub fn price(&self, upgrade: Upgrade) -> usize {
+        let (base, step) = upgrade.pricing();
+        base + step * self.count(upgrade)
+    }
+}
+
+#[derive(Component)]
+pub struct Workbench;
+
+#[derive(Component, Copy, Clone)]
+enum WorkshopButton {
+    Buy(Upgrade),
+    /// only in the sandbox
+    Lower(Upgrade),
+    Done,
+}
+
+/// The buddy currently on the workbench. The shop sets this when a team buddy is dropped on it.
+#[derive(Default)]
+pub struct Workshop {
+    buddy: Option<Entity>,
+}
+
+impl Workshop {
+    pub fn open(&mut self, buddy: Entity) {
+        self.buddy = Some(buddy);
+    }
+
+    pub fn close(&mut self) {
+        self.buddy = None;
+    }
+}
+
+/// A buddy trashed while it's on the workbench takes the panel with it, rather than leaving
+/// buttons for a buddy that's gone
+fn close_for_sold_buddy(mut workshop: ResMut<Workshop>, mut sold_events: EventReader<BuddySold>) {
+    for sold in sold_events.iter() {
+        if workshop.buddy == Some(sold.buddy) {
+            workshop.close();
+        }
+    }
+}
+
+struct WorkshopState {
+    workbench: Entity,
+    panel: Option<Entity>,
+}
+
+const WORKBENCH_COLOR: Color = Color::rgb(0.75, 0.6, 0.45);
+const UNAFFORDABLE_BUTTON: Color = Color::rgb(0.6, 0.6, 0.6);
+const TEXT_COLOR: Color = Color::rgb(0.2, 0.2, 0.2);
+
+fn spawn_workbench(mut commands: Commands, assets: Res<GameAssets>) {
+    let workbench = commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: WORKBENCH_COLOR,
+                ..Default::default()
+            },
+            texture: assets.pad.clone(),
+            transform: Transform::from_xyz(-450.0, -200.0, Z_BUDDY - 1.0)
+                .with_scale(Vec3::new(1.3, 1.3, 1.0)),
+            ..Default::default()
+        })
+        .insert(Workbench)
+        .with_children(|parent| {
+            parent.spawn_bundle(Text2dBundle {
+                text: Text::with_section(
+                    "Workshop",
+                    TextStyle {
+                        font: assets.title_font.clone(),
+                        font_size: 40.0,
+                        color: TEXT_COLOR,
+                    },
+                    TextAlignment {
+                        vertical: VerticalAlign::Center,
+                        horizontal: HorizontalAlign::Center,
+                    },
+                ),
+                transform: Transform::from_xyz(0.0, 40.0, 0.1),
+                ..Default::default()
+            });
+        })
+        .id();
+    commands.insert_resource(WorkshopState {
+        workbench,
+        panel: None,
+    });
+}
+
+fn show_workshop_panel(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    coins: Res<Coins>,
+    players: Res<Players>,
+    mut workshop: ResMut<Workshop>,
+    mut workshop_state: ResMut<WorkshopState>,
+    buddies: Query<(&BuddyName, &Upgrades), With<Buddy>>,
+    ui_root: Query<Entity, With<UiRoot>>,
+) {
+    // prices depend on whose turn it is, and are free in the sandbox
+    if !workshop.is_changed() && !coins.is_changed() && !players.is_changed() {
+        return;
+    }
+
+    if let Some(panel) = workshop_state.panel.take() {
+        commands.entity(panel).despawn_recursive();
+    }
+    let buddy = match workshop.buddy {
+        Some(buddy) => buddy,
+        None => return,
+    };
+    let (name, upgrades) = match buddies.get(buddy) {
+        Ok(buddy) => buddy,
+        // the buddy was trashed while on the workbench
+        Err(_) => {
+            workshop.buddy = None;
+            return;
+        }
+    };
+
+    let font = assets.title_font.clone();
+    let mut panel = None;
+    commands.entity(ui_root.single()).with_children(|parent| {
+        panel = Some(
+            parent
+                .spawn_bundle(NodeBundle {
+                    style: Style {
+                        size: Size::new(Val::Px(300.0), Val::Auto),
+                        position_type: PositionType::Absolute,
+                        // ui space is y-up, so this sits in the bottom right corner
+                        position: Rect {
+                            top: Val::Px(20.0),
+                            right: Val::Px(20.0),
+                            ..Default::default()
+                        },
+                        padding: Rect::all(Val::Px(10.0)),
+                        flex_direction: FlexDirection::ColumnReverse,
+                        align_items: AlignItems::Center,
+                        ..Default::default()
+                    },
+                    color: Color::rgba(0.95, 0.95, 1.0, 0.9).into(),
+                    ..Default::default()
+                })
+                .with_children(|parent| {
+                    parent.spawn_bundle(TextBundle {
+                        text: Text::with_section(
+                            format!("Workshop: {}", name.0),
+                            TextStyle {
+                                font: font.clone(),
+                                font_size: 40.0,
+                                color: TEXT_COLOR,
+                            },
+                            Default::default(),
+                        ),
+                        ..Default::default()
+                    });
+                    for upgrade in Upgrade::ALL.iter().copied() {
+                        if players.sandbox {
+                            spawn_workshop_button(
+                                parent,
+                                &font,
+                                upgrade.label(),
+                                WorkshopButton::Buy(upgrade),
+                                true,
+                            );
+                            if let Some(label) = upgrade.lower_label() {
+                                spawn_workshop_button(
+                                    parent,
+                                    &font,
+                                    label,
+                                    WorkshopButton::Lower(upgrade),
+                                    true,
+                                );
+                            }
+                            continue;
+                        }
+                        let price = upgrades.price(upgrade);
+                        spawn_workshop_button(
+                            parent,
+                            &font,
+                            &format!("{}  ({})", upgrade.label(), price),
+                            WorkshopButton::Buy(upgrade),
+                            price <= coins[players.turn],
+                        );
+                    }
+                    spawn_workshop_button(parent, &font, "Done", WorkshopButton::Done, true);
+                })
+                .id(),
+        );
+    });
+    workshop_state.panel = panel;
+}
+
+fn spawn_workshop_button(
+    parent: &mut ChildBuilder,
+    font: &Handle<Font>,
+    label: &str,
+    button: WorkshopButton,
+    enabled: bool,
+) {
+    let mut entity = parent.spawn_bundle(ButtonBundle {
+        style: Style {
+            size: Size::new(Val::Px(260.0), Val::Px(50.0)),
+            margin: Rect::all(Val::Px(4.0)),
+            // horizontally center child text
+            justify_content: JustifyContent::Center,
+            // vertically center child text
+            align_items: AlignItems::Center,
+            ..Default::default()
+        },
+        color: if enabled {
+            NORMAL_BUTTON.into()
+        } else {
+            UNAFFORDABLE_BUTTON.into()
+        },
+        ..Default::default()
+    });
+    entity.with_children(|parent| {
+        parent.spawn_bundle(TextBundle {
+            text: Text::with_section(
+                label,
+                TextStyle {
+                    font: font.clone(),
+                    font_size: 34.0,
+                    color: Color::rgb(0.9, 0.9, 0.9),
+                },
+                Default::default(),
+            ),
+            ..Default::default()
+        });
+    });
+    // unaffordable upgrades are shown, but can't be clicked
+    if enabled {
+        entity.insert(button);
+    }
+}
+
+fn workshop_buttons(
+    mut coins: ResMut<Coins>,
+    players: Res<Players>,
+    mut workshop: ResMut<Workshop>,
+    online: Option<Res<Online>>,
+    mut upgraded_events: EventWriter<BuddyUpgraded>,
+    mut interactions: Query<
+        (&Interaction, &WorkshopButton, &mut UiColor),
+        (Changed<Interaction>, With<Button>),
+    >,
+    mut buddies: Query<
+        (
+            &mut Health,
+            &mut Strength,
+            &mut BuddyFace,
+            &mut BuddyColor,
+            &mut Upgrades,
+        ),
+        With<Buddy>,
+    >,
+) {
+    let buddy = match workshop.buddy {
+        Some(buddy) => buddy,
+        None => return,
+    };
+    // upgrades after locking in wouldn't make it into the battle
+    if online.is_some_and(|online| online.locked_in()) {
+        return;
+    }
+    for (interaction, button, mut color) in interactions.iter_mut() {
+        match *interaction {
+            Interaction::Clicked => match button {
+                WorkshopButton::Buy(upgrade) => {
+                    if let Ok((mut health, mut strength, mut face, mut buddy_color, mut upgrades)) =
+                        buddies.get_mut(buddy)
+                    {
+                        // nothing is paid for in the sandbox, so there's nothing to count either
+                        if !players.sandbox {
+                            let price = upgrades.price(*upgrade);
+                            if price > coins[players.turn] {
+                                continue;
+                            }
+                            coins[players.turn] -= price;
+                            *upgrades.count_mut(*upgrade) += 1;
+                            upgraded_events.send(BuddyUpgraded {
+                                buddy,
+                                upgrade: *upgrade,
+                                price,
+                            });
+                        }
+                        // upgrades change the base values, so they survive the reset after battle
+                        match upgrade {
+                            Upgrade::Health => health.0.add_base(1),
+                            Upgrade::Strength => strength.0.add_base(1),
+                            Upgrade::Face => *face = face.next(),
+                            Upgrade::Recolor => *buddy_color = buddy_color.next(),
+                        }
+                    }
+                }
+                WorkshopButton::Lower(upgrade) => {
+                    if let Ok((mut health, mut strength, ..)) = buddies.get_mut(buddy) {
+                        // stats stop at 1, like the weakest buddies the shop rolls
+                        match upgrade {
+                            Upgrade::Health if health.0.base() > 1 => health.0.remove_base(1),
+                            Upgrade::Strength if strength.0.base() > 1 => strength.0.remove_base(1),
+                            _ => {}
+                        }
+                    }
+                }
+                WorkshopButton::Done => workshop.buddy = None,
+            },
+            Interaction::Hovered => *color = HOVERED_BUTTON.into(),
+            Interaction::None => *color = NORMAL_BUTTON.into(),
+        }
+    }
+}
+
+fn exit_workshop(
+    mut commands: Commands,
+    mut workshop: ResMut<Workshop>,
+    workshop_state: Res<WorkshopState>,
+) {
+    workshop.buddy = None;
+    commands
+        .entity(workshop_state.workbench)
+        .despawn_recursive();
+    if let Some(panel) = workshop_state.panel {
+        commands.entity(panel).despawn_recursive();
+    }
+    commands.remove_resource::<WorkshopState>();
+}
@@ -0,0 +1,17 @@
+#![allow(clippy::type_complexity, clippy::too_many_arguments)]
+
+pub mod audio;
+pub mod game;
+pub mod loading;
+pub mod menu;
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum AppState {
+    Loading,
+    Menu,
+    Startup,
+    Shop,
+    Battle,
+    /// between battles of a campaign
+    Map,
+}
@@ -0,0 +1,228 @@
+use crate::{
+    game::{
+        campaign::{Opponent, OpponentLoader, CAMPAIGN},
+        puzzle::{Puzzle, PuzzleLoader, PUZZLES},
+    },
+    AppState,
+};
+use bevy::{
+    asset::{HandleId, LoadState},
+    prelude::*,
+};
+
+pub struct LoadingPlugin;
+
+impl Plugin for LoadingPlugin {
+    fn build(&self, app: &mut App) {
+        // puzzles and campaign opponents are loaded along with everything else, so their loaders
+        // have to be in place first
+        app.add_asset::<Puzzle>()
+            .init_asset_loader::<PuzzleLoader>()
+            .add_asset::<Opponent>()
+            .init_asset_loader::<OpponentLoader>()
+            .init_resource::<GameAssets>()
+            .add_system_set(SystemSet::on_enter(AppState::Loading).with_system(spawn_loading))
+            .add_system_set(SystemSet::on_update(AppState::Loading).with_system(loading))
+            .add_system_set(SystemSet::on_exit(AppState::Loading).with_system(cleanup_loading));
+    }
+}
+
+/// Every texture and font the game uses. These are all loaded up front in [`AppState::Loading`],
+/// so everything else can grab handles from here instead of going through the [`AssetServer`].
+pub struct GameAssets {
+    pub foreground: Handle<Image>,
+    /// shown beside the title on the main menu
+    pub logo: Handle<Image>,
+    pub you_win: Handle<Image>,
+    pub you_lose: Handle<Image>,
+    pub you_tie: Handle<Image>,
+    pub pad: Handle<Image>,
+    pub trash: Handle<Image>,
+    pub battle_button: Handle<Image>,
+    pub money: Handle<Image>,
+    pub trophy: Handle<Image>,
+    pub price: Handle<Image>,
+    pub buddy_base: Handle<Image>,
+    pub buddy_outline: Handle<Image>,
+    pub buddy_health: Handle<Image>,
+    pub buddy_strength: Handle<Image>,
+    pub face_happy: Handle<Image>,
+    pub face_neutral: Handle<Image>,
+    pub face_blink: Handle<Image>,
+    /// faces for each [`Emotion`](crate::game::emotion::Emotion)
+    pub face_nervous: Handle<Image>,
+    pub face_angry: Handle<Image>,
+    pub face_hurt: Handle<Image>,
+    pub face_knocked_out: Handle<Image>,
+    pub face_celebrating: Handle<Image>,
+    /// see [`Accessory`](crate::game::appearance::Accessory)
+    pub accessory_hat: Handle<Image>,
+    pub accessory_horns: Handle<Image>,
+    pub accessory_ears: Handle<Image>,
+    /// used for titles, buttons and messages
+    pub title_font: Handle<Font>,
+    /// used for the numbers on counters and price tags
+    pub number_font: Handle<Font>,
+    /// every puzzle, in [`PUZZLES`] order
+    pub puzzles: Vec<Handle<Puzzle>>,
+    /// every campaign opponent, in [`CAMPAIGN`] order
+    pub opponents: Vec<Handle<Opponent>>,
+}
+
+impl FromWorld for GameAssets {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.get_resource::<AssetServer>().unwrap();
+        Self {
+            foreground: asset_server.load("foreground.png"),
+            logo: asset_server.load("logo.png"),
+            you_win: asset_server.load("you_win.png"),
+            you_lose: asset_server.load("you_lose.png"),
+            you_tie: asset_server.load("you_tie.png"),
+            pad: asset_server.load("pad.png"),
+            trash: asset_server.load("trash.png"),
+            battle_button: asset_server.load("battle_button.png"),
+            money: asset_server.load("money.png"),
+            trophy: asset_server.load("trophy.png"),
+            price: asset_server.load("price.png"),
+            buddy_base: asset_server.load("buddy/base.png"),
+            buddy_outline: asset_server.load("buddy/outline.png"),
+            buddy_health: asset_server.load("buddy/health.png"),
+            buddy_strength: asset_server.load("buddy/strength.png"),
+            face_happy: asset_server.load("buddy/face/happy.png"),
+            face_neutral: asset_server.load("buddy/face/neutral.png"),
+            face_blink: asset_server.load("buddy/face/blink.png"),
+            face_nervous: asset_server.load("buddy/face/nervous.png"),
+            face_angry: asset_server.load("buddy/face/angry.png"),
+            face_hurt: asset_server.load("buddy/face/hurt.png"),
+            face_knocked_out: asset_server.load("buddy/face/knocked_out.png"),
+            face_celebrating: asset_server.load("buddy/face/celebrating.png"),
+            accessory_hat: asset_server.load("buddy/accessory/hat.png"),
+            accessory_horns: asset_server.load("buddy/accessory/horns.png"),
+            accessory_ears: asset_server.load("buddy/accessory/ears.png"),
+            title_font: asset_server.load("font/AmaticSC-Bold.ttf"),
+            number_font: asset_server.load("font/CaveatBrush-Regular.ttf"),
+            puzzles: PUZZLES
+                .iter()
+                .map(|path| asset_server.load(*path))
+                .collect(),
+            opponents: CAMPAIGN
+                .iter()
+                .map(|path| asset_server.load(*path))
+                .collect(),
+        }
+    }
+}
+
+impl GameAssets {
+    fn handle_ids(&self) -> Vec<HandleId> {
+        let images = [
+            &self.foreground,
+            &self.logo,
+            &self.you_win,
+            &self.you_lose,
+            &self.you_tie,
+            &self.pad,
+            &self.trash,
+            &self.battle_button,
+            &self.money,
+            &self.trophy,
+            &self.price,
+            &self.buddy_base,
+            &self.buddy_outline,
+            &self.buddy_health,
+            &self.buddy_strength,
+            &self.face_happy,
+            &self.face_neutral,
+            &self.face_blink,
+            &self.face_nervous,
+            &self.face_angry,
+            &self.face_hurt,
+            &self.face_knocked_out,
+            &self.face_celebrating,
+            &self.accessory_hat,
+            &self.accessory_horns,
+            &self.accessory_ears,
+        ];
+        let fonts = [&self.title_font, &self.number_font];
+        images
+            .iter()
+            .map(|handle| handle.id)
+            .chain(fonts.iter().map(|handle| handle.id))
+            .chain(self.puzzles.iter().map(|handle| handle.id))
+            .chain(self.opponents.iter().map(|handle| handle.id))
+            .collect()
+    }
+}
+
+#[derive(Component)]
+struct LoadingText;
+
+struct LoadingData {
+    root: Entity,
+}
+
+fn spawn_loading(mut commands: Commands, assets: Res<GameAssets>) {
+    let root = commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            color: Color::NONE.into(),
+            ..Default::default()
+        })
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(TextBundle {
+                    text: Text::with_section(
+                        "Loading",
+                        TextStyle {
+                            // text waits for its font to load, so this shows up as soon as it can
+                            font: assets.title_font.clone(),
+                            font_size: 60.0,
+                            color: Color::rgb(0.2, 0.2, 0.2),
+                        },
+                        Default::default(),
+                    ),
+                    ..Default::default()
+                })
+                .insert(LoadingText);
+        })
+        .id();
+    commands.insert_resource(LoadingData { root });
+}
+
+fn loading(
+    asset_server: Res<AssetServer>,
+    assets: Res<GameAssets>,
+    mut state: ResMut<State<AppState>>,
+    mut texts: Query<&mut Text, With<LoadingText>>,
+) {
+    let handle_ids = assets.handle_ids();
+    match asset_server.get_group_load_state(handle_ids.iter().copied()) {
+        LoadState::Loaded => {
+            state.set(AppState::Menu).unwrap();
+        }
+        LoadState::Failed => {
+            // a missing texture is better than never getting past the loading screen
+            error!("some assets failed to load");
+            state.set(AppState::Menu).unwrap();
+        }
+        _ => {
+            let loaded = handle_ids
+                .iter()
+                .filter(|id| asset_server.get_load_state(**id) == LoadState::Loaded)
+                .count();
+            for mut text in texts.iter_mut() {
+                text.sections[0].value = format!("Loading {} / {}", loaded, handle_ids.len());
+            }
+        }
+    }
+}
+
+fn cleanup_loading(mut commands: Commands, loading_data: Res<LoadingData>) {
+    commands.entity(loading_data.root).despawn_recursive();
+    commands.remove_resource::<LoadingData>();
+}
@@ -1,10 +1,7 @@
-#![allow(clippy::type_complexity, clippy::too_many_arguments)]
-
-mod game;
-mod menu;
-
-use crate::{game::GamePlugin, menu::MenuPlugin};
 use bevy::prelude::*;
+use build_a_better_buddy::{
+    audio::SoundPlugin, game::GamePlugin, loading::LoadingPlugin, menu::MenuPlugin, AppState,
+};
 
 fn main() {
     App::new()
@@ -13,22 +10,21 @@ fn main() {
             ..Default::default()
         })
         .insert_resource(ClearColor(Color::rgb(0.8, 0.8, 0.9)))
-        .add_state(AppState::Startup)
-        .add_plugins(DefaultPlugins)
+        .add_state(AppState::Loading)
+        .add_plugins_with(DefaultPlugins, |plugins| {
+            // SoundPlugin plays through its own rodio stream on desktop
+            #[cfg(not(target_arch = "wasm32"))]
+            plugins.disable::<bevy::audio::AudioPlugin>();
+            plugins
+        })
+        .add_plugin(LoadingPlugin)
         .add_plugin(MenuPlugin)
         .add_plugin(GamePlugin)
+        .add_plugin(SoundPlugin)
         .add_startup_system(setup)
         .run();
 }
 
-#[derive(Debug, Clone, Eq, PartialEq, Hash)]
-pub enum AppState {
-    Menu,
-    Startup,
-    Shop,
-    Battle,
-}
-
 fn setup(mut commands: Commands) {
     commands.spawn_bundle(OrthographicCameraBundle::new_2d());
     commands.spawn_bundle(UiCameraBundle::default());
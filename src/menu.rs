@@ -1,34 +1,642 @@
-use bevy::prelude::*;
-
-use crate::AppState;
+use crate::{
+    audio::AudioSettings,
+    game::{
+        achievements::{Achievement, AchievementProgress},
+        buddy::{BuddyTemplate, Side},
+        campaign::{Campaign, Opponent},
+        daily::{DailyChallenge, DailyScores},
+        hot_seat::Players,
+        online::PendingOnline,
+        puzzle::{Puzzle, PuzzleProgress, PuzzleRun},
+        replay::{Replay, ReplayPlayback},
+        rules::{Rule, Rules},
+        run_rng::RunRng,
+        save::RunSave,
+        stats::Stats,
+    },
+    loading::GameAssets,
+    AppState,
+};
+use bevy::{app::AppExit, prelude::*};
+use rand::Rng;
 
 pub struct MenuPlugin;
 
 impl Plugin for MenuPlugin {
     fn build(&self, app: &mut App) {
         app.add_system_set(SystemSet::on_enter(AppState::Menu).with_system(spawn_menu))
-            .add_system_set(SystemSet::on_update(AppState::Menu).with_system(menu))
+            .add_system_set(
+                SystemSet::on_update(AppState::Menu)
+                    .with_system(menu)
+                    .with_system(drift_menu_buddies),
+            )
             .add_system_set(SystemSet::on_exit(AppState::Menu).with_system(cleanup_menu));
     }
 }
 
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum MenuScreen {
+    Title,
+    /// ways to play other than a normal run
+    Modes,
+    Daily,
+    Puzzles,
+    /// waiting to hear back from the relay
+    Connecting,
+    /// starting an online game failed because the relay isn't running
+    RelayOffline,
+    Stats,
+    Replays,
+    Settings,
+    Credits,
+}
+
+impl MenuScreen {
+    /// the screen going back leads to
+    fn parent(&self) -> MenuScreen {
+        match self {
+            MenuScreen::Daily
+            | MenuScreen::Puzzles
+            | MenuScreen::Connecting
+            | MenuScreen::RelayOffline => MenuScreen::Modes,
+            _ => MenuScreen::Title,
+        }
+    }
+}
+
 struct MenuData {
-    button_entity: Entity,
+    root: Entity,
+    screen: MenuScreen,
+    panel: Entity,
+    /// buttons on the current screen, in keyboard navigation order
+    buttons: Vec<Entity>,
+    focus: usize,
+    buddies: Vec<Entity>,
 }
 
-#[derive(Component)]
-pub struct PlayButton;
+#[derive(Component, Copy, Clone, PartialEq, Eq)]
+pub enum MenuButton {
+    Continue,
+    NewGame,
+    Campaign,
+    Modes,
+    Daily,
+    PlayDaily,
+    Puzzles,
+    PlayPuzzle(usize),
+    LocalVersus,
+    OnlineVersus,
+    Sandbox,
+    Stats,
+    Replays,
+    WatchReplay(usize),
+    Settings,
+    Credits,
+    Quit,
+    Back,
+    VolumeDown(VolumeChannel),
+    VolumeUp(VolumeChannel),
+    ToggleRule(Rule),
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum VolumeChannel {
+    Master,
+    Music,
+    Sfx,
+}
+
+impl VolumeChannel {
+    const ALL: &'static [VolumeChannel] = &[
+        VolumeChannel::Master,
+        VolumeChannel::Music,
+        VolumeChannel::Sfx,
+    ];
+
+    fn label(&self) -> &'static str {
+        match self {
+            VolumeChannel::Master => "Master",
+            VolumeChannel::Music => "Music",
+            VolumeChannel::Sfx => "Effects",
+        }
+    }
+
+    fn volume(&self, settings: &AudioSettings) -> f32 {
+        match self {
+            VolumeChannel::Master => settings.master,
+            VolumeChannel::Music => settings.music,
+            VolumeChannel::Sfx => settings.sfx,
+        }
+    }
+
+    fn volume_mut<'a>(&self, settings: &'a mut AudioSettings) -> &'a mut f32 {
+        match self {
+            VolumeChannel::Master => &mut settings.master,
+            VolumeChannel::Music => &mut settings.music,
+            VolumeChannel::Sfx => &mut settings.sfx,
+        }
+    }
+}
+
+const VOLUME_STEP: f32 = 0.1;
 
 pub const NORMAL_BUTTON: Color = Color::rgb(0.4, 0.4, 0.8);
 pub const HOVERED_BUTTON: Color = Color::rgb(0.6, 0.6, 0.9);
+const TEXT_COLOR: Color = Color::rgb(0.2, 0.2, 0.2);
 
-fn spawn_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
-    let button_entity = commands
+fn spawn_menu(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    audio_settings: Res<AudioSettings>,
+    rules: Res<Rules>,
+    stats: Res<Stats>,
+    achievements: Res<AchievementProgress>,
+    daily_scores: Res<DailyScores>,
+    puzzles: Res<Assets<Puzzle>>,
+    puzzle_progress: Res<PuzzleProgress>,
+) {
+    let root = commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                // ui space is y-up, so reverse the column to lay things out top to bottom
+                flex_direction: FlexDirection::ColumnReverse,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            color: Color::NONE.into(),
+            ..Default::default()
+        })
+        .id();
+
+    let (panel, buttons) = spawn_panel(
+        &mut commands,
+        &assets,
+        root,
+        MenuScreen::Title,
+        &audio_settings,
+        &rules,
+        &stats,
+        &achievements,
+        &daily_scores,
+        &puzzles,
+        &puzzle_progress,
+    );
+
+    let mut rng = rand::thread_rng();
+    let buddies = (0..5)
+        .map(|i| {
+            let velocity: f32 = rng.gen_range(40.0..90.0) * if rng.gen() { 1.0 } else { -1.0 };
+            let x = -500.0 + i as f32 * 250.0 + rng.gen_range(-40.0..40.0);
+            let y = rng.gen_range(-300.0..-240.0);
+            let side = if velocity > 0.0 {
+                Side::Left
+            } else {
+                Side::Right
+            };
+            let round = rng.gen_range(1..5);
+            let buddy = BuddyTemplate::random_for_round(&mut rng, round).spawn(
+                &mut commands,
+                &assets,
+                0,
+                side,
+                Transform::from_xyz(x, y, 0.0),
+            );
+            commands.entity(buddy).insert(MenuBuddy { velocity });
+            buddy
+        })
+        .collect();
+
+    commands.insert_resource(MenuData {
+        root,
+        screen: MenuScreen::Title,
+        panel,
+        buttons,
+        focus: 0,
+        buddies,
+    });
+}
+
+fn spawn_panel(
+    commands: &mut Commands,
+    assets: &GameAssets,
+    root: Entity,
+    screen: MenuScreen,
+    audio_settings: &AudioSettings,
+    rules: &Rules,
+    stats: &Stats,
+    achievements: &AchievementProgress,
+    daily_scores: &DailyScores,
+    puzzles: &Assets<Puzzle>,
+    puzzle_progress: &PuzzleProgress,
+) -> (Entity, Vec<Entity>) {
+    let font = assets.title_font.clone();
+    let mut buttons = Vec::new();
+    let mut panel = None;
+    commands.entity(root).with_children(|parent| {
+        panel = Some(
+            parent
+                .spawn_bundle(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::ColumnReverse,
+                        align_items: AlignItems::Center,
+                        ..Default::default()
+                    },
+                    color: Color::NONE.into(),
+                    ..Default::default()
+                })
+                .with_children(|parent| match screen {
+                    MenuScreen::Title => {
+                        parent
+                            .spawn_bundle(NodeBundle {
+                                style: Style {
+                                    flex_direction: FlexDirection::Row,
+                                    align_items: AlignItems::Center,
+                                    ..Default::default()
+                                },
+                                color: Color::NONE.into(),
+                                ..Default::default()
+                            })
+                            .with_children(|parent| {
+                                parent.spawn_bundle(ImageBundle {
+                                    style: Style {
+                                        size: Size::new(Val::Px(201.0), Val::Px(89.0)),
+                                        margin: Rect::all(Val::Px(4.0)),
+                                        ..Default::default()
+                                    },
+                                    image: UiImage(assets.logo.clone()),
+                                    ..Default::default()
+                                });
+                                spawn_text(parent, &font, "Build A Better Buddy", 110.0);
+                            });
+                        if RunSave::exists() {
+                            spawn_button(
+                                parent,
+                                &font,
+                                "Continue",
+                                MenuButton::Continue,
+                                &mut buttons,
+                            );
+                        }
+                        spawn_button(parent, &font, "New Game", MenuButton::NewGame, &mut buttons);
+                        spawn_button(
+                            parent,
+                            &font,
+                            "Campaign",
+                            MenuButton::Campaign,
+                            &mut buttons,
+                        );
+                        spawn_button(parent, &font, "Modes", MenuButton::Modes, &mut buttons);
+                        spawn_button(parent, &font, "Stats", MenuButton::Stats, &mut buttons);
+                        spawn_button(parent, &font, "Replays", MenuButton::Replays, &mut buttons);
+                        spawn_button(
+                            parent,
+                            &font,
+                            "Settings",
+                            MenuButton::Settings,
+                            &mut buttons,
+                        );
+                        spawn_button(parent, &font, "Credits", MenuButton::Credits, &mut buttons);
+                        // browsers don't let pages close themselves
+                        #[cfg(not(target_arch = "wasm32"))]
+                        spawn_button(parent, &font, "Quit", MenuButton::Quit, &mut buttons);
+                    }
+                    MenuScreen::Modes => {
+                        spawn_text(parent, &font, "Modes", 80.0);
+                        spawn_button(
+                            parent,
+                            &font,
+                            "Daily Challenge",
+                            MenuButton::Daily,
+                            &mut buttons,
+                        );
+                        spawn_button(parent, &font, "Puzzles", MenuButton::Puzzles, &mut buttons);
+                        spawn_text(parent, &font, "Take turns on this computer", 34.0);
+                        spawn_button(
+                            parent,
+                            &font,
+                            "Local Versus",
+                            MenuButton::LocalVersus,
+                            &mut buttons,
+                        );
+                        // browsers can't open plain sockets to reach the relay
+                        #[cfg(not(target_arch = "wasm32"))]
+                        spawn_text(
+                            parent,
+                            &font,
+                            "Or play through a relay: cargo run --bin bab-relay",
+                            34.0,
+                        );
+                        #[cfg(not(target_arch = "wasm32"))]
+                        spawn_button(
+                            parent,
+                            &font,
+                            "Online Versus",
+                            MenuButton::OnlineVersus,
+                            &mut buttons,
+                        );
+                        spawn_text(parent, &font, "Build both teams and watch them fight", 34.0);
+                        spawn_button(parent, &font, "Sandbox", MenuButton::Sandbox, &mut buttons);
+                        spawn_button(parent, &font, "Back", MenuButton::Back, &mut buttons);
+                    }
+                    MenuScreen::Daily => {
+                        let daily = DailyChallenge::today();
+                        spawn_text(parent, &font, "Daily Challenge", 80.0);
+                        spawn_text(
+                            parent,
+                            &font,
+                            &format!("{}: {}", daily.date, daily.modifier.description()),
+                            44.0,
+                        );
+                        let best = match daily_scores.best(daily.date) {
+                            Some(best) => format!(
+                                "Your best today: {} trophies by round {}",
+                                best.trophies, best.rounds
+                            ),
+                            None => "Everyone gets the same shops today".to_string(),
+                        };
+                        spawn_text(parent, &font, &best, 34.0);
+                        spawn_button(parent, &font, "Play", MenuButton::PlayDaily, &mut buttons);
+                        let earlier = daily_scores
+                            .days
+                            .iter()
+                            .filter(|score| score.date != daily.date)
+                            .take(5)
+                            .collect::<Vec<_>>();
+                        if !earlier.is_empty() {
+                            spawn_text(parent, &font, "Earlier days", 44.0);
+                        }
+                        for score in earlier {
+                            spawn_text(
+                                parent,
+                                &font,
+                                &format!(
+                                    "{}: {} trophies by round {}",
+                                    score.date, score.trophies, score.rounds
+                                ),
+                                30.0,
+                            );
+                        }
+                        spawn_button(parent, &font, "Back", MenuButton::Back, &mut buttons);
+                    }
+                    MenuScreen::Puzzles => {
+                        spawn_text(parent, &font, "Puzzles", 80.0);
+                        for (index, handle) in assets.puzzles.iter().enumerate() {
+                            // a puzzle file that failed to load is left out
+                            let puzzle = match puzzles.get(handle) {
+                                Some(puzzle) => puzzle,
+                                None => continue,
+                            };
+                            if !puzzle_progress.is_unlocked(index) {
+                                spawn_text(parent, &font, &format!("{}. Locked", index + 1), 34.0);
+                                continue;
+                            }
+                            let solved = if puzzle_progress.is_solved(index) {
+                                " (solved)"
+                            } else {
+                                ""
+                            };
+                            spawn_button_with_size(
+                                parent,
+                                &font,
+                                &format!("{}. {}{}", index + 1, puzzle.name, solved),
+                                MenuButton::PlayPuzzle(index),
+                                Size::new(Val::Px(620.0), Val::Px(55.0)),
+                                &mut buttons,
+                            );
+                        }
+                        spawn_button(parent, &font, "Back", MenuButton::Back, &mut buttons);
+                    }
+                    MenuScreen::Connecting => {
+                        spawn_text(parent, &font, "Connecting to the relay...", 80.0);
+                        spawn_button(parent, &font, "Back", MenuButton::Back, &mut buttons);
+                    }
+                    MenuScreen::RelayOffline => {
+                        spawn_text(parent, &font, "Couldn't reach the relay", 80.0);
+                        spawn_text(
+                            parent,
+                            &font,
+                            "Start it with cargo run --bin bab-relay, then try again",
+                            34.0,
+                        );
+                        spawn_button(
+                            parent,
+                            &font,
+                            "Try Again",
+                            MenuButton::OnlineVersus,
+                            &mut buttons,
+                        );
+                        spawn_button(parent, &font, "Back", MenuButton::Back, &mut buttons);
+                    }
+                    MenuScreen::Stats => {
+                        spawn_text(parent, &font, "Stats", 80.0);
+                        for line in [
+                            format!("Battles: {}", stats.battles),
+                            format!(
+                                "Wins / Losses / Ties: {} / {} / {}",
+                                stats.wins, stats.losses, stats.ties
+                            ),
+                            format!("Most trophies: {}", stats.most_trophies),
+                            format!("Buddies bought: {}", stats.buddies_bought),
+                            format!("Buddies trashed: {}", stats.buddies_trashed),
+                            format!("Foes defeated: {}", stats.foes_defeated),
+                            format!("Coins earned: {}", stats.coins_earned),
+                        ] {
+                            spawn_text(parent, &font, &line, 34.0);
+                        }
+                        spawn_text(parent, &font, "Achievements", 50.0);
+                        for achievement in Achievement::ALL {
+                            let mark = if achievements.is_unlocked(*achievement) {
+                                "[x]"
+                            } else {
+                                "[  ]"
+                            };
+                            let line = format!(
+                                "{} {} - {}",
+                                mark,
+                                achievement.title(),
+                                achievement.description()
+                            );
+                            spawn_text(parent, &font, &line, 30.0);
+                        }
+                        spawn_button(parent, &font, "Back", MenuButton::Back, &mut buttons);
+                    }
+                    MenuScreen::Replays => {
+                        spawn_text(parent, &font, "Replays", 80.0);
+                        let replays = Replay::load_all();
+                        if replays.is_empty() {
+                            spawn_text(parent, &font, "No battles recorded yet", 40.0);
+                        }
+                        for replay in replays {
+                            spawn_button_with_size(
+                                parent,
+                                &font,
+                                &replay.label(),
+                                MenuButton::WatchReplay(replay.number),
+                                Size::new(Val::Px(620.0), Val::Px(55.0)),
+                                &mut buttons,
+                            );
+                        }
+                        spawn_button(parent, &font, "Back", MenuButton::Back, &mut buttons);
+                    }
+                    MenuScreen::Settings => {
+                        spawn_text(parent, &font, "Settings", 80.0);
+                        for channel in VolumeChannel::ALL {
+                            let volume = channel.volume(audio_settings);
+                            parent
+                                .spawn_bundle(NodeBundle {
+                                    style: Style {
+                                        flex_direction: FlexDirection::Row,
+                                        align_items: AlignItems::Center,
+                                        ..Default::default()
+                                    },
+                                    color: Color::NONE.into(),
+                                    ..Default::default()
+                                })
+                                .with_children(|parent| {
+                                    let label = format!(
+                                        "{}: {}%",
+                                        channel.label(),
+                                        (volume * 100.0).round()
+                                    );
+                                    spawn_text(parent, &font, &label, 40.0);
+                                    spawn_small_button(
+                                        parent,
+                                        &font,
+                                        "-",
+                                        MenuButton::VolumeDown(*channel),
+                                        &mut buttons,
+                                    );
+                                    spawn_small_button(
+                                        parent,
+                                        &font,
+                                        "+",
+                                        MenuButton::VolumeUp(*channel),
+                                        &mut buttons,
+                                    );
+                                });
+                        }
+                        spawn_text(parent, &font, "Rules", 50.0);
+                        for rule in Rule::ALL {
+                            parent
+                                .spawn_bundle(NodeBundle {
+                                    style: Style {
+                                        flex_direction: FlexDirection::Row,
+                                        align_items: AlignItems::Center,
+                                        ..Default::default()
+                                    },
+                                    color: Color::NONE.into(),
+                                    ..Default::default()
+                                })
+                                .with_children(|parent| {
+                                    spawn_text(parent, &font, rule.label(), 40.0);
+                                    let toggle = if rule.enabled(rules) { "On" } else { "Off" };
+                                    spawn_small_button(
+                                        parent,
+                                        &font,
+                                        toggle,
+                                        MenuButton::ToggleRule(*rule),
+                                        &mut buttons,
+                                    );
+                                });
+                        }
+                        spawn_button(parent, &font, "Back", MenuButton::Back, &mut buttons);
+                    }
+                    MenuScreen::Credits => {
+                        spawn_text(parent, &font, "Credits", 80.0);
+                        spawn_text(
+                            parent,
+                            &font,
+                            "Built with Bevy Engine for Bevy Jam #1",
+                            40.0,
+                        );
+                        spawn_text(parent, &font, "Fonts", 50.0);
+                        spawn_text(parent, &font, "Amatic SC by Vernon Adams", 34.0);
+                        spawn_text(parent, &font, "Caveat Brush by Pablo Impallari", 34.0);
+                        spawn_text(parent, &font, "Indie Flower by Kimberly Geswein", 34.0);
+                        spawn_text(
+                            parent,
+                            &font,
+                            "All licensed under the SIL Open Font License 1.1",
+                            30.0,
+                        );
+                        spawn_button(parent, &font, "Back", MenuButton::Back, &mut buttons);
+                    }
+                })
+                .id(),
+        );
+    });
+
+    (panel.unwrap(), buttons)
+}
+
+fn spawn_text(parent: &mut ChildBuilder, font: &Handle<Font>, value: &str, font_size: f32) {
+    parent.spawn_bundle(TextBundle {
+        style: Style {
+            margin: Rect::all(Val::Px(4.0)),
+            ..Default::default()
+        },
+        text: Text::with_section(
+            value,
+            TextStyle {
+                font: font.clone(),
+                font_size,
+                color: TEXT_COLOR,
+            },
+            Default::default(),
+        ),
+        ..Default::default()
+    });
+}
+
+fn spawn_button(
+    parent: &mut ChildBuilder,
+    font: &Handle<Font>,
+    label: &str,
+    button: MenuButton,
+    buttons: &mut Vec<Entity>,
+) {
+    spawn_button_with_size(
+        parent,
+        font,
+        label,
+        button,
+        Size::new(Val::Px(220.0), Val::Px(55.0)),
+        buttons,
+    );
+}
+
+fn spawn_small_button(
+    parent: &mut ChildBuilder,
+    font: &Handle<Font>,
+    label: &str,
+    button: MenuButton,
+    buttons: &mut Vec<Entity>,
+) {
+    spawn_button_with_size(
+        parent,
+        font,
+        label,
+        button,
+        Size::new(Val::Px(55.0), Val::Px(55.0)),
+        buttons,
+    );
+}
+
+fn spawn_button_with_size(
+    parent: &mut ChildBuilder,
+    font: &Handle<Font>,
+    label: &str,
+    button: MenuButton,
+    size: Size<Val>,
+    buttons: &mut Vec<Entity>,
+) {
+    let entity = parent
         .spawn_bundle(ButtonBundle {
             style: Style {
-                size: Size::new(Val::Px(150.0), Val::Px(65.0)),
-                // center button
-                margin: Rect::all(Val::Auto),
+                size,
+                margin: Rect::all(Val::Px(5.0)),
                 // horizontally center child text
                 justify_content: JustifyContent::Center,
                 // vertically center child text
@@ -38,13 +646,13 @@ fn spawn_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
             color: NORMAL_BUTTON.into(),
             ..Default::default()
         })
-        .insert(PlayButton)
+        .insert(button)
         .with_children(|parent| {
             parent.spawn_bundle(TextBundle {
                 text: Text::with_section(
-                    "Play",
+                    label,
                     TextStyle {
-                        font: asset_server.load("font/AmaticSC-Bold.ttf"),
+                        font: font.clone(),
                         font_size: 40.0,
                         color: Color::rgb(0.9, 0.9, 0.9),
                     },
@@ -54,32 +662,251 @@ fn spawn_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
             });
         })
         .id();
-
-    commands.insert_resource(MenuData { button_entity });
+    buttons.push(entity);
 }
 
 fn menu(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
     mut state: ResMut<State<AppState>>,
-    mut interaction_query: Query<
-        (&Interaction, &mut UiColor),
-        (Changed<Interaction>, With<PlayButton>),
-    >,
+    mut menu_data: ResMut<MenuData>,
+    mut audio_settings: ResMut<AudioSettings>,
+    mut rules: ResMut<Rules>,
+    stats: Res<Stats>,
+    achievements: Res<AchievementProgress>,
+    daily_scores: Res<DailyScores>,
+    puzzles: Res<Assets<Puzzle>>,
+    puzzle_progress: Res<PuzzleProgress>,
+    opponents: Res<Assets<Opponent>>,
+    // paired up to stay within the number of parameters a system can have
+    (keyboard, pending_online): (Res<Input<KeyCode>>, Option<Res<PendingOnline>>),
+    mut app_exit: EventWriter<AppExit>,
+    interactions: Query<(Entity, &Interaction), (Changed<Interaction>, With<MenuButton>)>,
+    mut buttons: Query<(&MenuButton, &mut UiColor)>,
 ) {
-    for (interaction, mut color) in interaction_query.iter_mut() {
+    // an attempt the player backed out of is thrown away once it's over
+    let mut relay_screen = None;
+    if let Some(result) = pending_online.and_then(|pending| pending.finished()) {
+        commands.remove_resource::<PendingOnline>();
+        match result {
+            Ok(online) if menu_data.screen == MenuScreen::Connecting => {
+                commands.insert_resource(Players::default());
+                commands.insert_resource(online);
+                state.set(AppState::Startup).unwrap();
+            }
+            Ok(_) => {}
+            Err(err) => {
+                warn!("couldn't reach the relay: {}", err);
+                if menu_data.screen == MenuScreen::Connecting {
+                    relay_screen = Some(MenuScreen::RelayOffline);
+                }
+            }
+        }
+    }
+
+    let mut activated = None;
+    for (entity, interaction) in interactions.iter() {
         match *interaction {
             Interaction::Clicked => {
-                state.set(AppState::Startup).unwrap();
+                activated = buttons.get_component::<MenuButton>(entity).ok().copied();
             }
             Interaction::Hovered => {
-                *color = HOVERED_BUTTON.into();
+                if let Some(index) = menu_data.buttons.iter().position(|b| *b == entity) {
+                    menu_data.focus = index;
+                }
+            }
+            Interaction::None => {}
+        }
+    }
+
+    let button_count = menu_data.buttons.len();
+    if keyboard.just_pressed(KeyCode::Up) || keyboard.just_pressed(KeyCode::W) {
+        menu_data.focus = (menu_data.focus + button_count - 1) % button_count;
+    }
+    if keyboard.just_pressed(KeyCode::Down) || keyboard.just_pressed(KeyCode::S) {
+        menu_data.focus = (menu_data.focus + 1) % button_count;
+    }
+    if keyboard.just_pressed(KeyCode::Return) || keyboard.just_pressed(KeyCode::Space) {
+        activated = buttons
+            .get_component::<MenuButton>(menu_data.buttons[menu_data.focus])
+            .ok()
+            .copied();
+    }
+    if keyboard.just_pressed(KeyCode::Escape) && menu_data.screen != MenuScreen::Title {
+        activated = Some(MenuButton::Back);
+    }
+
+    for (index, entity) in menu_data.buttons.iter().enumerate() {
+        if let Ok(mut color) = buttons.get_component_mut::<UiColor>(*entity) {
+            *color = if index == menu_data.focus {
+                HOVERED_BUTTON.into()
+            } else {
+                NORMAL_BUTTON.into()
+            };
+        }
+    }
+
+    let next_screen = match activated {
+        Some(MenuButton::Continue) => {
+            if let Some(run) = RunSave::load() {
+                commands.insert_resource(run);
+                state.set(AppState::Startup).unwrap();
+            }
+            None
+        }
+        Some(MenuButton::NewGame) => {
+            RunSave::delete();
+            state.set(AppState::Startup).unwrap();
+            None
+        }
+        // the campaign isn't saved, so the single player run is left alone
+        Some(MenuButton::Campaign) => {
+            // an opponent file that failed to load is skipped
+            let opponents = assets
+                .opponents
+                .iter()
+                .filter_map(|handle| opponents.get(handle))
+                .cloned()
+                .collect::<Vec<_>>();
+            if !opponents.is_empty() {
+                commands.insert_resource(Campaign::new(opponents));
+                state.set(AppState::Startup).unwrap();
+            }
+            None
+        }
+        Some(MenuButton::Modes) => Some(MenuScreen::Modes),
+        Some(MenuButton::Daily) => Some(MenuScreen::Daily),
+        // daily challenges aren't saved, so the single player run is left alone
+        Some(MenuButton::PlayDaily) => {
+            let daily = DailyChallenge::today();
+            commands.insert_resource(daily.rules());
+            commands.insert_resource(RunRng::from_seed(daily.seed));
+            commands.insert_resource(daily);
+            state.set(AppState::Startup).unwrap();
+            None
+        }
+        Some(MenuButton::Puzzles) => Some(MenuScreen::Puzzles),
+        // puzzles aren't saved either, and bring their own budget and rules
+        Some(MenuButton::PlayPuzzle(index)) => {
+            if let Some(puzzle) = puzzles.get(&assets.puzzles[index]) {
+                commands.insert_resource(puzzle.rules());
+                commands.insert_resource(Players::default());
+                commands.insert_resource(PuzzleRun::new(index, puzzle.clone()));
+                state.set(AppState::Startup).unwrap();
             }
-            Interaction::None => {
-                *color = NORMAL_BUTTON.into();
+            None
+        }
+        // versus games aren't saved, so the single player run is left alone
+        Some(MenuButton::LocalVersus) => {
+            commands.insert_resource(Players::hot_seat());
+            state.set(AppState::Startup).unwrap();
+            None
+        }
+        Some(MenuButton::OnlineVersus) => {
+            commands.insert_resource(PendingOnline::connect(&rules));
+            Some(MenuScreen::Connecting)
+        }
+        // the sandbox isn't saved either
+        Some(MenuButton::Sandbox) => {
+            commands.insert_resource(Players::sandbox());
+            state.set(AppState::Startup).unwrap();
+            None
+        }
+        Some(MenuButton::Stats) => Some(MenuScreen::Stats),
+        Some(MenuButton::Replays) => Some(MenuScreen::Replays),
+        Some(MenuButton::WatchReplay(number)) => {
+            if let Some(replay) = Replay::load(number) {
+                commands.insert_resource(ReplayPlayback::new(replay));
+                state.set(AppState::Startup).unwrap();
             }
+            None
+        }
+        Some(MenuButton::Settings) => Some(MenuScreen::Settings),
+        Some(MenuButton::Credits) => Some(MenuScreen::Credits),
+        Some(MenuButton::Quit) => {
+            app_exit.send(AppExit);
+            None
+        }
+        Some(MenuButton::Back) => Some(menu_data.screen.parent()),
+        Some(MenuButton::VolumeDown(channel)) => {
+            adjust_volume(channel.volume_mut(&mut audio_settings), -VOLUME_STEP);
+            Some(MenuScreen::Settings)
+        }
+        Some(MenuButton::VolumeUp(channel)) => {
+            adjust_volume(channel.volume_mut(&mut audio_settings), VOLUME_STEP);
+            Some(MenuScreen::Settings)
+        }
+        Some(MenuButton::ToggleRule(rule)) => {
+            let enabled = rule.enabled_mut(&mut rules);
+            *enabled = !*enabled;
+            Some(MenuScreen::Settings)
+        }
+        None => relay_screen,
+    };
+
+    if let Some(screen) = next_screen {
+        commands.entity(menu_data.panel).despawn_recursive();
+        let (panel, buttons) = spawn_panel(
+            &mut commands,
+            &assets,
+            menu_data.root,
+            screen,
+            &audio_settings,
+            &rules,
+            &stats,
+            &achievements,
+            &daily_scores,
+            &puzzles,
+            &puzzle_progress,
+        );
+        // keep the focus in place when a screen is rebuilt in place (ex: changing the volume)
+        if screen != menu_data.screen {
+            menu_data.focus = 0;
+        }
+        menu_data.screen = screen;
+        menu_data.panel = panel;
+        menu_data.buttons = buttons;
+    }
+}
+
+fn adjust_volume(volume: &mut f32, amount: f32) {
+    // round to whole steps so repeated presses don't drift
+    *volume = ((*volume + amount) / VOLUME_STEP).round() * VOLUME_STEP;
+    *volume = volume.clamp(0.0, 1.0);
+}
+
+/// Idle buddies that wander along the bottom of the title screen
+#[derive(Component)]
+struct MenuBuddy {
+    velocity: f32,
+}
+
+const MENU_BUDDY_BOUNDS: f32 = 560.0;
+
+fn drift_menu_buddies(
+    time: Res<Time>,
+    mut buddies: Query<(&mut Transform, &mut MenuBuddy, &mut Side)>,
+) {
+    for (mut transform, mut buddy, mut side) in buddies.iter_mut() {
+        transform.translation.x += buddy.velocity * time.delta_seconds();
+        if transform.translation.x.abs() > MENU_BUDDY_BOUNDS
+            && transform.translation.x.signum() == buddy.velocity.signum()
+        {
+            buddy.velocity = -buddy.velocity;
+            // buddies face right on the left side and left on the right side
+            *side = if buddy.velocity > 0.0 {
+                Side::Left
+            } else {
+                Side::Right
+            };
         }
     }
 }
 
 fn cleanup_menu(mut commands: Commands, menu_data: Res<MenuData>) {
-    commands.entity(menu_data.button_entity).despawn_recursive();
+    commands.entity(menu_data.root).despawn_recursive();
+    for buddy in menu_data.buddies.iter() {
+        commands.entity(*buddy).despawn_recursive();
+    }
+    commands.remove_resource::<MenuData>();
 }